@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::{Args, Parser, Subcommand, ValueHint};
+use clap::{Args, Parser, Subcommand, ValueEnum, ValueHint};
 use clap_verbosity_flag::Verbosity;
 
 pub fn parse_cli() -> Cli {
@@ -27,6 +27,8 @@ pub struct Cli {
 
 #[derive(Subcommand, Debug)]
 pub enum Commands {
+    #[command(about = "Remove the compiled files of all problems in a folder")]
+    Clean(Clean),
     #[command(about = "Commands to help you configure kat")]
     Config(Config),
     #[command(about = "Get a problem from kattis")]
@@ -35,6 +37,22 @@ pub enum Commands {
     Init(Init),
     #[command(about = "Open a problem in the browser")]
     Open(Open),
+    #[command(
+        about = "Compile and run a solution on your own input, without comparing its output"
+    )]
+    Run(Run),
+    #[command(
+        about = "Run a solution on a single test under a debugger, valgrind or another wrapper"
+    )]
+    Debug(Debug),
+    #[command(
+        about = "Stress test a problem by comparing it with a reference solution on generated inputs"
+    )]
+    Stress(Stress),
+    #[command(
+        about = "Benchmark a solution by running each test many times, optionally compared with another solution"
+    )]
+    Bench(Bench),
     #[command(about = "Submit a problem to kattis")]
     Submit(Submit),
     #[command(about = "Test a problem against its test case(s)")]
@@ -45,6 +63,16 @@ pub enum Commands {
     Watch(Watch),
 }
 
+#[derive(Args, Debug)]
+pub struct Clean {
+    #[arg(
+        default_value = ".",
+        value_hint = ValueHint::DirPath,
+        help = "The path of the folder (or problem) to clean. All problems in it are cleaned, and by default, the current directory is used."
+    )]
+    pub path: PathBuf,
+}
+
 #[derive(Args, Debug)]
 pub struct Config {
     #[command(subcommand)]
@@ -111,6 +139,229 @@ pub struct Open {
     pub problem: Option<String>,
 }
 
+#[derive(Args, Debug)]
+pub struct Run {
+    #[arg(
+        default_value = ".",
+        value_hint = ValueHint::DirPath,
+        help = "The path of the problem (folder) you want to run. By default, the current directory is used."
+    )]
+    pub path: PathBuf,
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "The path of the solution file to run. If not specified, the first file with the same name as the problem in the problem folder will be used.
+If multiple files with the correct extension are found, you will be prompted to choose which one to use."
+    )]
+    pub file: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        help = "The programming language to run the problem with. 
+This can be used to override the default language set in the configuration file, or the language decided by the extension of the file given with --file."
+    )]
+    pub language: Option<String>,
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "The file to use as the input of the program. If not specified, the input is read from stdin, e.g. typed in the terminal."
+    )]
+    pub input: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "If set, the problem is compiled even if it has not changed since it was last compiled."
+    )]
+    pub rebuild: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct Debug {
+    #[arg(help = "The test to run, given by its number or name, e.g. 3 or secret/big.in.")]
+    pub test: String,
+    #[arg(
+        default_value = ".",
+        value_hint = ValueHint::DirPath,
+        help = "The path of the problem (folder) you want to debug. By default, the current directory is used."
+    )]
+    pub path: PathBuf,
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "The path of the solution file to debug. If not specified, the first file with the same name as the problem in the problem folder will be used.
+If multiple files with the correct extension are found, you will be prompted to choose which one to use."
+    )]
+    pub file: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        help = "The programming language to debug the problem with. 
+This can be used to override the default language set in the configuration file, or the language decided by the extension of the file given with --file."
+    )]
+    pub language: Option<String>,
+    #[arg(
+        short,
+        long,
+        help = "The command to run the solution with, e.g. \"valgrind\" or \"gdb --args\", which is put in front of the execute command of the language.
+If it contains {input_file}, that is replaced by the path of the test input, which is then not redirected to stdin.
+If not specified, the debug_wrapper of the language is used."
+    )]
+    pub wrap: Option<String>,
+    #[arg(
+        long,
+        help = "If set, the problem is compiled even if it has not changed since it was last compiled."
+    )]
+    pub rebuild: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct Bench {
+    #[arg(
+        default_value = ".",
+        value_hint = ValueHint::DirPath,
+        help = "The path of the problem (folder) you want to benchmark. By default, the current directory is used."
+    )]
+    pub path: PathBuf,
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "The path of the solution file to benchmark. If not specified, the first file with the same name as the problem in the problem folder will be used.
+If multiple files with the correct extension are found, you will be prompted to choose which one to use."
+    )]
+    pub file: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        help = "The programming language to benchmark the problem with. 
+This can be used to override the default language set in the configuration file, or the language decided by the extension of the file given with --file."
+    )]
+    pub language: Option<String>,
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "Another solution file to compare the solution with, e.g. a faster version of it. Its language is decided by its file extension."
+    )]
+    pub compare: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        help = "The test case(s) to benchmark on, as a comma separated list of numbers, ranges, names, globs or groups,
+e.g. '1', '1-3', '1,3-5', 'secret/3.in', 'custom/*' or 'sample'. If not specified, all test cases will be used."
+    )]
+    pub test_cases: Option<String>,
+    #[arg(
+        short = 'n',
+        long,
+        default_value_t = 10,
+        value_parser = clap::value_parser!(u32).range(1..),
+        help = "The number of timed runs of each test."
+    )]
+    pub runs: u32,
+    #[arg(
+        short,
+        long,
+        default_value_t = 1,
+        help = "The number of runs of each test before the timed runs, e.g. to fill the file system cache."
+    )]
+    pub warmup: u32,
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "The time limit for each run in seconds.
+If not specified, the time limit of the problem (scaled by the time_multiplier of the language) will be used."
+    )]
+    pub time_limit: Option<f64>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = BuildProfile::Release,
+        help = "The build profile to compile the solutions with."
+    )]
+    pub profile: BuildProfile,
+    #[arg(
+        long,
+        help = "If set, the solutions are compiled even if they have not changed since they were last compiled."
+    )]
+    pub rebuild: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct Stress {
+    #[arg(
+        default_value = ".",
+        value_hint = ValueHint::DirPath,
+        help = "The path of the problem (folder) you want to stress test. By default, the current directory is used."
+    )]
+    pub path: PathBuf,
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "The path of the solution file to test. If not specified, the first file with the same name as the problem in the problem folder will be used.
+If multiple files with the correct extension are found, you will be prompted to choose which one to use."
+    )]
+    pub file: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        help = "The programming language to test the problem against. 
+This can be used to override the default language set in the configuration file, or the language decided by the extension of the file given with --file."
+    )]
+    pub language: Option<String>,
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "The path of the generator, which is run with a seed as its only argument and should print a test input.
+If not specified, a file named generator or gen (in any configured language) in the problem folder will be used."
+    )]
+    pub generator: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "The path of the reference solution, e.g. a slow brute-force solution, whose output is used as the answer to each input.
+If not specified, a file named reference or brute (in any configured language) in the problem folder will be used."
+    )]
+    pub reference: Option<PathBuf>,
+    #[arg(
+        short = 'n',
+        long,
+        default_value_t = 1000,
+        help = "The maximum number of inputs to generate."
+    )]
+    pub iterations: u64,
+    #[arg(
+        long,
+        help = "The seed given to the generator for the first input, the next inputs get the following seeds.
+If not specified, a random seed is used."
+    )]
+    pub seed: Option<u64>,
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "The time limit for each input in seconds.
+If not specified, the time limit of the problem (scaled by the time_multiplier of the language) will be used."
+    )]
+    pub time_limit: Option<f64>,
+    #[arg(
+        long,
+        value_name = "FLAGS",
+        help = "The flags for the output validator, in the same format as on Kattis, e.g. 'float_tolerance 1e-6'.
+If not specified, the validator flags of the problem will be used."
+    )]
+    pub validator_flags: Option<String>,
+    #[arg(
+        long,
+        help = "If set, the programs are compiled even if they have not changed since they were last compiled."
+    )]
+    pub rebuild: bool,
+}
+
 #[derive(Args, Debug)]
 pub struct Submit {
     #[arg(
@@ -132,7 +383,7 @@ If multiple files with the correct extension are found, you will be prompted to
         short,
         long,
         help = "The programming language to test the problem against. 
-This can be used to override the default language set in the configuration file."
+This can be used to override the default language set in the configuration file, or the language decided by the extension of the file given with --file."
     )]
     pub language: Option<String>,
     #[arg(
@@ -157,10 +408,21 @@ If the problem fails any of the test cases, the submission will be aborted."
         help = "If set, the submission will be opened in the browser after all tests have been run on the kattis server."
     )]
     pub open: bool,
+    #[arg(
+        long,
+        value_name = "FORMAT=PATH",
+        value_parser = parse_report,
+        requires = "test_first",
+        help = "Write a report of the local test results to a file when using --test-first, e.g. 'junit=report.xml' for a JUnit XML report."
+    )]
+    pub report: Option<ReportOption>,
 }
 
 #[derive(Args, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct Test {
+    #[command(subcommand)]
+    pub command: Option<TestCommands>,
     #[arg(
         default_value = ".",
         value_hint = ValueHint::DirPath,
@@ -178,24 +440,126 @@ If multiple files with the correct extension are found, you will be prompted to
     #[arg(
         short,
         long,
-        help = "The id(s) of the test case(s) to test against. 
-If not specified, all test cases will be tested, e.g. '1', '1-3', or '1,3-5'."
+        help = "The test case(s) to test against, as a comma separated list of numbers, ranges, names, globs or groups,
+e.g. '1', '1-3', '1,3-5', 'secret/3.in', 'custom/*' or 'sample'. If not specified, all test cases will be tested."
     )]
     pub test_cases: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "test_cases",
+        help = "If set, only the test cases that failed the last time the problem was tested are tested."
+    )]
+    pub failed: bool,
     #[arg(
         short,
         long,
         help = "The programming language to test the problem against. 
-This can be used to override the default language set in the configuration file."
+This can be used to override the default language set in the configuration file, or the language decided by the extension of the file given with --file."
     )]
     pub language: Option<String>,
     #[arg(
         short,
         long,
         default_value_t = false,
+        conflicts_with = "format",
         help = "If set, try to submit the problem to kattis if all tests pass."
     )]
     pub submit: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = OutputFormat::Text,
+        help = "The format of the test results. With 'json', only a summary of the results is printed as JSON, e.g. for scripts."
+    )]
+    pub format: OutputFormat,
+    #[arg(
+        long,
+        value_name = "TEST",
+        conflicts_with_all = ["submit", "format", "bless", "save_output"],
+        help = "Minimize the input of a failing test, e.g. '3' or '3.in', by removing lines and tokens from it as long as it fails in the same way.
+The smallest input that still fails is saved as a new test. To minimize a wrong answer with the default output validator,
+the problem folder needs a reference solution (named reference or brute) to create the answers of the smaller inputs."
+    )]
+    pub minimize: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["file", "language", "submit", "format", "minimize", "bless", "report"],
+        help = "Test every solution file of the problem, i.e. every file with the problem id in its name and the extension of any configured language,
+each with its own language, and show a comparison of the verdicts and times of the solutions."
+    )]
+    pub all_solutions: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ShrinkStrategy::Auto,
+        requires = "minimize",
+        help = "How to remove parts of the input with --minimize. 'counted' removes items from inputs where the first line is the number of items,
+and updates that number, while 'auto' also tries to remove single lines and tokens."
+    )]
+    pub shrink: ShrinkStrategy,
+    #[command(flatten)]
+    pub options: TestOptions,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TestCommands {
+    #[command(
+        about = "Add a test case to a problem, with an answer from a file or from a reference solution"
+    )]
+    Add(TestAdd),
+}
+
+#[derive(Args, Debug)]
+pub struct TestAdd {
+    #[arg(
+        default_value = ".",
+        value_hint = ValueHint::DirPath,
+        help = "The path of the problem (folder) you want to add a test to. By default, the current directory is used."
+    )]
+    pub path: PathBuf,
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "The file with the input of the test. If not specified, the input is read from stdin."
+    )]
+    pub input: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+        conflicts_with = "reference",
+        help = "The file with the expected answer of the test.
+If not specified, the answer is created by running the reference solution on the input."
+    )]
+    pub answer: Option<PathBuf>,
+    #[arg(
+        short,
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "The path of the reference solution to create the answer with.
+If not specified, a file named reference or brute (in any configured language) in the problem folder will be used."
+    )]
+    pub reference: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "If set, the test is added to the custom folder of the tests, so that it is labelled as a custom test in the test results."
+    )]
+    pub custom: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum ShrinkStrategy {
+    Auto,
+    Counted,
+    Lines,
+    Tokens,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
 }
 
 #[derive(Args, Debug)]
@@ -218,15 +582,143 @@ If multiple files with the correct extension are found, you will be prompted to
     #[arg(
         short,
         long,
-        help = "The id(s) of the test case(s) to test against.
-If not specified, all test cases will be tested, e.g. '1', '1-3', or '1,3-5'."
+        help = "The test case(s) to test against, as a comma separated list of numbers, ranges, names, globs or groups,
+e.g. '1', '1-3', '1,3-5', 'secret/3.in', 'custom/*' or 'sample'. If not specified, all test cases will be tested."
     )]
     pub test_cases: Option<String>,
     #[arg(
         short,
         long,
         help = "The programming language to test the problem against. 
-This can be used to override the default language set in the configuration file."
+This can be used to override the default language set in the configuration file, or the language decided by the extension of the file given with --file."
     )]
     pub language: Option<String>,
+    #[command(flatten)]
+    pub options: TestOptions,
+}
+
+#[derive(Args, Debug, Default, Clone)]
+pub struct TestOptions {
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        help = "The time limit for each test case in seconds.
+If not specified, the time limit of the problem (scaled by the time_multiplier of the language) will be used."
+    )]
+    pub time_limit: Option<f64>,
+    #[arg(
+        long,
+        value_name = "MEGABYTES",
+        help = "The memory limit for each test case in megabytes.
+If not specified, the memory limit of the problem will be used."
+    )]
+    pub memory_limit: Option<u64>,
+    #[arg(
+        long,
+        value_name = "FLAGS",
+        help = "The flags for the output validator, in the same format as on Kattis, e.g. 'float_tolerance 1e-6'.
+The supported flags are case_sensitive, space_change_sensitive, float_tolerance, float_absolute_tolerance and float_relative_tolerance.
+If not specified, the validator flags of the problem will be used."
+    )]
+    pub validator_flags: Option<String>,
+    #[arg(
+        long,
+        value_hint = ValueHint::FilePath,
+        help = "The path of the interactor to test an interactive problem with, relative to the problem folder.
+The interactor is run with the arguments `input judge_answer feedback_dir`, and its stdin and stdout are connected to the stdout and stdin of your program.
+If not specified, the interactor of the problem will be used (if any)."
+    )]
+    pub interactor: Option<String>,
+    #[arg(
+        long,
+        default_value_t = false,
+        help = "If set, the full transcript of the communication between your program and the interactor is shown for interactive problems."
+    )]
+    pub transcript: bool,
+    #[arg(
+        short,
+        long,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        help = "The number of test cases to run in parallel. By default, the number of available CPUs is used.
+Running tests in parallel can affect their timing, so use --jobs 1 if a test is close to the time limit."
+    )]
+    pub jobs: Option<usize>,
+    #[arg(
+        long,
+        help = "If set, the reports of the failed tests are shown together after the run, in the pager set in $PAGER (or less) if they do not fit in the terminal."
+    )]
+    pub pager: bool,
+    #[arg(
+        long,
+        help = "If set, the problem is compiled even if it has not changed since it was last compiled."
+    )]
+    pub rebuild: bool,
+    #[arg(
+        long,
+        help = "If set, no more test cases are started after the first one fails."
+    )]
+    pub fail_fast: bool,
+    #[arg(
+        long,
+        help = "If set, the output of the program is saved as the answer of each test, including tests without an answer file.
+Only use this when you trust the output, e.g. to create the answers of your own tests with a correct solution."
+    )]
+    pub bless: bool,
+    #[arg(
+        long,
+        help = "If set, the output of the program for each test is saved in a .out file in the .kat/output folder of the problem."
+    )]
+    pub save_output: bool,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = BuildProfile::Release,
+        help = "The build profile to compile the problem with. The debug profile uses the compile_command_debug of the language, e.g. with sanitizers,
+and keeps its build apart from the release build, so that switching between them does not compile the problem again."
+    )]
+    pub profile: BuildProfile,
+    #[arg(
+        long,
+        value_name = "FORMAT=PATH",
+        value_parser = parse_report,
+        help = "Write a report of the test results to a file, e.g. 'junit=report.xml' for a JUnit XML report.
+{problem} in the path is replaced by the problem id, which is useful when testing many problems."
+    )]
+    pub report: Option<ReportOption>,
+}
+
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq)]
+pub enum BuildProfile {
+    #[default]
+    Release,
+    Debug,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Junit,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportOption {
+    pub format: ReportFormat,
+    pub path: PathBuf,
+}
+
+fn parse_report(value: &str) -> Result<ReportOption, String> {
+    let (format, path) = value
+        .split_once('=')
+        .ok_or("expected FORMAT=PATH, e.g. junit=report.xml")?;
+    let format = match format {
+        "junit" => ReportFormat::Junit,
+        _ => {
+            return Err(format!(
+                "unknown report format '{format}', the supported formats are: junit"
+            ))
+        }
+    };
+    Ok(ReportOption {
+        format,
+        path: PathBuf::from(path),
+    })
 }
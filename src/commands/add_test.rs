@@ -0,0 +1,103 @@
+use crate::{
+    cli::{TestAdd, TestOptions},
+    commands::{
+        stress::{find_program, run_reference},
+        test::{compile_helper_program, TestSettings},
+    },
+    utils::{find_problem_dir, save_test, TestCase, CUSTOM_TEST_GROUP},
+    App,
+};
+
+use std::{
+    fs,
+    io::{self, IsTerminal, Read},
+};
+
+use color_eyre::{
+    eyre::{self, Context},
+    Report,
+};
+use colored::Colorize;
+
+/// Adds a test to a problem with `kat test add`, numbered after the existing tests
+pub fn add_test(app: &App, args: &TestAdd) -> Result<(), Report> {
+    let (problem_path, _) = find_problem_dir(app, &args.path)?;
+    let work_dir = tempfile::tempdir().wrap_err("🙀 Failed to create a temporary directory")?;
+
+    let input = match &args.input {
+        Some(input) => {
+            if !input.exists() {
+                eyre::bail!("🙀 The input file {} does not exist!", input.display());
+            }
+            input.clone()
+        }
+        None => {
+            if io::stdin().is_terminal() {
+                println!("⌨️ Type the input of the test, and end it with Ctrl+D:");
+            }
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .wrap_err("🙀 Failed to read the input from stdin")?;
+            let input_path = work_dir.path().join("test.in");
+            fs::write(&input_path, input).wrap_err("🙀 Failed to write the input")?;
+            input_path
+        }
+    };
+
+    let answer = match &args.answer {
+        Some(answer) => {
+            if !answer.exists() {
+                eyre::bail!("🙀 The answer file {} does not exist!", answer.display());
+            }
+            answer.clone()
+        }
+        None => {
+            let reference = find_program(
+                app,
+                &problem_path,
+                &args.reference,
+                "reference solution",
+                &["reference", "brute"],
+            )?;
+            let settings = TestSettings::resolve(
+                app,
+                &reference.language,
+                &problem_path,
+                &TestOptions::default(),
+            )?;
+            compile_helper_program(
+                app,
+                &reference,
+                &problem_path,
+                "reference solution",
+                &settings,
+            )?;
+            let output = run_reference(app, &reference, &input, &settings)?;
+            if !output.status.success() {
+                eyre::bail!(
+                    "🙀 The reference solution {} failed with {} on the input\n{}",
+                    reference.file_name(),
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr)
+                );
+            }
+            let answer_path = work_dir.path().join("test.ans");
+            fs::write(&answer_path, &output.stdout).wrap_err("🙀 Failed to write the answer")?;
+            answer_path
+        }
+    };
+
+    let test = TestCase {
+        input,
+        answer: Some(answer),
+        group: None,
+    };
+    let group = args.custom.then_some(CUSTOM_TEST_GROUP);
+    let saved_test = save_test(&problem_path, &test, group)?;
+    println!(
+        "{}",
+        format!("📝 Added the test {}", saved_test.display()).bright_green()
+    );
+    Ok(())
+}
@@ -0,0 +1,148 @@
+use crate::{
+    cli::Test,
+    commands::test::{test_problem, TestRun, TestSettings},
+    utils::{errors::KatError, find_solution_files, verdict::Verdict, TestCase},
+    App,
+};
+
+use std::path::Path;
+
+use color_eyre::{eyre, Report};
+use colored::Colorize;
+
+/// Tests every solution of the problem with `kat test --all-solutions`, and compares their results
+pub fn test_all_solutions(
+    app: &App,
+    args: &Test,
+    problem_id: &str,
+    problem_path: &Path,
+    tests: Vec<TestCase>,
+) -> Result<(), Report> {
+    let solutions = find_solution_files(app, problem_path, problem_id)?;
+    if solutions.is_empty() {
+        eyre::bail!(
+            "🙀 No solution files found in directory: {}",
+            problem_path.display()
+        );
+    }
+
+    let mut runs = Vec::new();
+    for (file_path, language) in &solutions {
+        let file_name = file_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        println!(
+            "{}",
+            format!(
+                "🧪 Testing problem: {problem_id} with the file {file_name} ({language}) ...\n"
+            )
+            .bold()
+            .bright_blue()
+        );
+        let mut settings = TestSettings::resolve(app, language, problem_path, &args.options)?;
+        // the failed tests of one solution say nothing about the failed tests of the solution that is tested next
+        settings.remember_failed_tests = false;
+        // a solution that does not compile should not keep the other solutions from being tested
+        let run = match test_problem(
+            app,
+            problem_id,
+            problem_path,
+            file_path,
+            tests.clone(),
+            language,
+            &settings,
+        ) {
+            Ok(run) => Some(run),
+            Err(e) if matches!(e.downcast_ref(), Some(KatError::CompileError { .. })) => {
+                println!("{}", e.to_string().bright_red());
+                None
+            }
+            Err(e) => return Err(e),
+        };
+        println!();
+        runs.push((file_name, run));
+    }
+
+    print_comparison(&tests, &runs);
+
+    if runs
+        .iter()
+        .all(|(_, run)| run.as_ref().is_some_and(|run| run.passed))
+    {
+        println!(
+            "{}",
+            format!(
+                "🏁 All {} solutions of {} passed all tests!",
+                runs.len(),
+                problem_id.underline()
+            )
+            .bright_green()
+        );
+        Ok(())
+    } else {
+        println!(
+            "{}",
+            "❌ Some solutions failed some of the tests!".bright_red()
+        );
+        Err(KatError::TestsFailed.into())
+    }
+}
+
+/// Prints a table with a row for each test and a column for each solution, with the verdict and time of each test
+fn print_comparison(tests: &[TestCase], runs: &[(String, Option<TestRun>)]) {
+    let name_width = tests
+        .iter()
+        .map(|test| test.name().len())
+        .max()
+        .unwrap_or_default()
+        .max("passed".len());
+    let column_width = runs
+        .iter()
+        .map(|(file_name, _)| file_name.len())
+        .max()
+        .unwrap_or_default()
+        .max(10);
+
+    let header: String = runs
+        .iter()
+        .map(|(file_name, _)| format!("  {file_name:<column_width$}"))
+        .collect();
+    println!("{}", format!("{:<name_width$}{header}", "").bold());
+
+    for test in tests {
+        let cells: String = runs
+            .iter()
+            .map(|(_, run)| {
+                let (emoji, text) = match run {
+                    None => (Verdict::CompileError.emoji(), "compile".to_string()),
+                    Some(run) => match run.results.iter().find(|(t, _)| t.name() == test.name()) {
+                        None => ("⏭️", "skipped".to_string()),
+                        Some((_, result)) => (
+                            result.verdict.emoji(),
+                            result.output.as_ref().map_or("-".to_string(), |output| {
+                                format!("{:.2}s", output.wall_time.as_secs_f64())
+                            }),
+                        ),
+                    },
+                };
+                // only the text is padded, as emojis take up two columns however many characters they are made of
+                format!("  {emoji} {text:<width$}", width = column_width - 3)
+            })
+            .collect();
+        println!("{:<name_width$}{cells}", test.name());
+    }
+
+    let totals: String = runs
+        .iter()
+        .map(|(_, run)| {
+            let total = match run {
+                Some(run) => format!("{}/{}", run.results.len() - run.failed_tests(), tests.len()),
+                None => format!("0/{}", tests.len()),
+            };
+            format!("  {total:<column_width$}")
+        })
+        .collect();
+    println!("{}\n", format!("{:<name_width$}{totals}", "passed").bold());
+}
@@ -0,0 +1,299 @@
+use crate::{
+    cli::{Bench, TestOptions},
+    commands::test::{compile_all, compile_problem, execute_problem, HelperProgram, TestSettings},
+    utils::{errors::KatError, find_problem_dir, find_test_files, get_problem_file, TestCase},
+    App,
+};
+
+use std::path::{Path, PathBuf};
+
+use color_eyre::{eyre, Report};
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+
+/// A solution that is benchmarked, with the settings of its language
+struct Solution {
+    name: String,
+    file_path: PathBuf,
+    language: String,
+    settings: TestSettings,
+}
+
+/// Statistics of the CPU time of the timed runs of a test, in seconds
+struct Stats {
+    min: f64,
+    median: f64,
+    mean: f64,
+    stddev: f64,
+}
+
+impl Solution {
+    fn new(
+        app: &App,
+        file_path: PathBuf,
+        language: String,
+        problem_path: &Path,
+        options: &TestOptions,
+    ) -> Result<Self, Report> {
+        let settings = TestSettings::resolve(app, &language, problem_path, options)?;
+        Ok(Solution {
+            name: file_path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            file_path,
+            language,
+            settings,
+        })
+    }
+}
+
+impl Stats {
+    fn new(mut samples: Vec<f64>) -> Self {
+        samples.sort_by(f64::total_cmp);
+        let count = samples.len() as f64;
+        let middle = samples.len() / 2;
+        let median = if samples.len().is_multiple_of(2) {
+            (samples[middle - 1] + samples[middle]) / 2.0
+        } else {
+            samples[middle]
+        };
+        let mean = samples.iter().sum::<f64>() / count;
+        // the sample standard deviation, as the runs are a sample of all the runs the solution could make
+        let stddev = if samples.len() > 1 {
+            (samples
+                .iter()
+                .map(|time| (time - mean).powi(2))
+                .sum::<f64>()
+                / (count - 1.0))
+                .sqrt()
+        } else {
+            0.0
+        };
+        Stats {
+            min: samples[0],
+            median,
+            mean,
+            stddev,
+        }
+    }
+}
+
+/// Runs each test many times with `kat bench`, and reports statistics of the CPU time of the solution on each test,
+/// side by side with those of the solution it is compared with, if any
+pub async fn bench(app: &App, args: &Bench) -> Result<(), Report> {
+    let (problem_path, problem_id) = find_problem_dir(app, &args.path)?;
+    let (_, problem_file_path, language) =
+        get_problem_file(app, &args.file, &args.language, &problem_path, &problem_id)?;
+    let tests = find_test_files(app, &args.test_cases, &problem_path, false)?;
+    let options = TestOptions {
+        time_limit: args.time_limit,
+        rebuild: args.rebuild,
+        profile: args.profile,
+        ..Default::default()
+    };
+
+    let mut solutions = vec![Solution::new(
+        app,
+        problem_file_path,
+        language,
+        &problem_path,
+        &options,
+    )?];
+    if let Some(compare) = &args.compare {
+        if !compare.exists() {
+            eyre::bail!("🙀 The solution {} does not exist!", compare.display());
+        }
+        let program = HelperProgram::new(app, compare.clone(), Vec::new())?;
+        solutions.push(Solution::new(
+            app,
+            program.file_path,
+            program.language,
+            &problem_path,
+            &options,
+        )?);
+    }
+    if solutions[0].settings.interactor.is_some() {
+        eyre::bail!("🙀 Benchmarking interactive problems is not supported!");
+    }
+
+    let names: Vec<&str> = solutions
+        .iter()
+        .map(|solution| solution.name.as_str())
+        .collect();
+    println!(
+        "{}",
+        format!(
+            "📊 Benchmarking problem: {} with the file {} ...\n",
+            problem_id,
+            names.join(" and ")
+        )
+        .bold()
+        .bright_blue()
+    );
+
+    for (i, solution) in solutions.iter().enumerate() {
+        let compile_command = app.config.kat_config.languages[&solution.language]
+            .compile_command_for(solution.settings.profile)
+            .unwrap_or_default();
+        if i == 0 {
+            compile_all(
+                app,
+                &problem_id,
+                &problem_path,
+                &solution.file_path,
+                compile_command,
+                &solution.settings,
+            )?;
+        } else if !compile_command.is_empty() {
+            compile_problem(
+                compile_command,
+                &problem_path,
+                &solution.file_path,
+                &format!("solution: {}", solution.name),
+                &solution.settings,
+            )?;
+        }
+    }
+
+    let runs_per_test = (args.warmup + args.runs) as u64;
+    let mut results = Vec::new();
+    for solution in &solutions {
+        let pb = ProgressBar::new(tests.len() as u64 * runs_per_test);
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("{bar:30} {pos}/{len} {msg}")
+                .unwrap()
+                .progress_chars("=> "),
+        );
+        let mut stats = Vec::new();
+        for test in &tests {
+            pb.set_message(format!("{} on {}", solution.name, test.name()));
+            match time_test(app, solution, test, args, &pb)? {
+                Some(test_stats) => stats.push(Some(test_stats)),
+                // a solution that fails is not worth timing any further
+                None => break,
+            }
+        }
+        pb.finish_and_clear();
+        stats.resize_with(tests.len(), || None);
+        results.push(stats);
+    }
+
+    println!(
+        "\n⏱️ CPU time over {} runs of each test (after {} warm-up run(s)):",
+        args.runs, args.warmup
+    );
+    let width = names
+        .iter()
+        .map(|name| name.len())
+        .max()
+        .unwrap_or_default();
+    for (i, test) in tests.iter().enumerate() {
+        println!("{}", test.name().bold());
+        for (solution, stats) in solutions.iter().zip(&results) {
+            let line = match &stats[i] {
+                Some(test_stats) => format!(
+                    "median {:.3}s  mean {:.3}s  min {:.3}s  stddev {:.3}s",
+                    test_stats.median, test_stats.mean, test_stats.min, test_stats.stddev
+                ),
+                None => "failed".bright_red().to_string(),
+            };
+            println!("  {:<width$}  {line}", solution.name);
+        }
+        if let [first, second] = &results[..] {
+            if let (Some(first), Some(second)) = (&first[i], &second[i]) {
+                println!("  {}", compare(first.median, second.median, &names));
+            }
+        }
+    }
+
+    let failed = results
+        .iter()
+        .any(|stats| stats.iter().any(|test_stats| test_stats.is_none()));
+    if failed {
+        println!(
+            "{}",
+            "❌ Some solutions failed some of the tests!".bright_red()
+        );
+        return Err(KatError::TestsFailed.into());
+    }
+
+    let totals: Vec<f64> = results
+        .iter()
+        .map(|stats| {
+            stats
+                .iter()
+                .flatten()
+                .map(|test_stats| test_stats.median)
+                .sum()
+        })
+        .collect();
+    let total_times: Vec<String> = names
+        .iter()
+        .zip(&totals)
+        .map(|(name, total)| format!("{name} {total:.3}s"))
+        .collect();
+    println!(
+        "\n{}",
+        format!(
+            "🏁 The median CPU times of all tests add up to: {}",
+            total_times.join(", ")
+        )
+        .bright_green()
+    );
+    if let [first, second] = totals[..] {
+        println!("{}", compare(first, second, &names));
+    }
+    Ok(())
+}
+
+/// Runs the test the warm-up runs and then the timed runs, and returns the statistics of the timed runs.
+/// If the solution fails the test, the report of the failure is printed instead.
+fn time_test(
+    app: &App,
+    solution: &Solution,
+    test: &TestCase,
+    args: &Bench,
+    pb: &ProgressBar,
+) -> Result<Option<Stats>, Report> {
+    let execute_command = &app.config.kat_config.languages[&solution.language].execute_command;
+    let mut samples = Vec::new();
+    for run in 0..args.warmup + args.runs {
+        let result = execute_problem(
+            app,
+            execute_command,
+            &solution.file_path,
+            test,
+            &solution.settings,
+        )?;
+        pb.inc(1);
+        if !result.passed() {
+            pb.suspend(|| println!("{}", result.report));
+            return Ok(None);
+        }
+        if run >= args.warmup {
+            if let Some(output) = result.output {
+                samples.push(output.cpu_time().as_secs_f64());
+            }
+        }
+    }
+    Ok(Some(Stats::new(samples)))
+}
+
+/// Describes how much faster the second solution is than the first, given the times of both
+fn compare(first: f64, second: f64, names: &[&str]) -> String {
+    if first <= 0.0 || second <= 0.0 {
+        return "🤷 The times are too short to compare".to_string();
+    }
+    if second <= first {
+        format!("🚀 {} is {:.2}x faster", names[1], first / second)
+            .bright_green()
+            .to_string()
+    } else {
+        format!("🐢 {} is {:.2}x slower", names[1], second / first)
+            .bright_yellow()
+            .to_string()
+    }
+}
@@ -0,0 +1,122 @@
+use crate::{
+    cli::Clean,
+    utils::{config::ProblemConfig, find_build_directory},
+    App,
+};
+
+use std::{
+    env::current_dir,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use color_eyre::{eyre::Context, Report};
+
+use colored::Colorize;
+
+pub async fn clean(app: &App, args: &Clean) -> Result<(), Report> {
+    let current_dir = current_dir().wrap_err("🙀 Failed to get current directory")?;
+    let workspace = if args.path == Path::new(".") {
+        current_dir
+    } else {
+        current_dir.join(&args.path)
+    };
+    let workspace = workspace.canonicalize().wrap_err(format!(
+        "🙀 Failed to find the directory {}",
+        workspace.display()
+    ))?;
+
+    let mut problem_paths = Vec::new();
+    find_problem_dirs(&workspace, true, &mut problem_paths)?;
+    let workspace_kat_dir = workspace.join(".kat").canonicalize().ok();
+
+    let mut cleaned = 0;
+    for problem_path in problem_paths {
+        let problem_config = ProblemConfig::load(&problem_path)?;
+        let build_directory =
+            find_build_directory(app, &problem_path, &problem_config.build_directory)?;
+
+        // the paths are canonicalized, as a build directory like `..` or a symlink could otherwise point anywhere.
+        // Only build directories inside the problem or the .kat directory of the workspace are ever removed.
+        if let Ok(canonical_build_directory) = build_directory.canonicalize() {
+            let canonical_problem_path = problem_path
+                .canonicalize()
+                .wrap_err("🙀 Failed to find the problem directory")?;
+            let is_removable =
+                is_strictly_inside(&canonical_build_directory, &canonical_problem_path)
+                    || workspace_kat_dir.as_ref().is_some_and(|kat_dir| {
+                        is_strictly_inside(&canonical_build_directory, kat_dir)
+                    });
+            if is_removable {
+                fs::remove_dir_all(&canonical_build_directory).wrap_err(format!(
+                    "🙀 Failed to remove the build directory {}",
+                    build_directory.display()
+                ))?;
+                println!("🧹 Removed {}", build_directory.display());
+                cleaned += 1;
+            } else {
+                println!(
+                    "{}",
+                    format!(
+                        "🙅 Not removing the build directory {}, as it is not inside the problem {} or the .kat directory of the workspace",
+                        canonical_build_directory.display(),
+                        problem_path.display()
+                    )
+                    .bright_yellow()
+                );
+            }
+        }
+
+        // without the build, the build stamps are useless
+        let kat_dir = problem_path.join(".kat");
+        let _ = fs::remove_dir_all(kat_dir.join("cache"));
+        let _ = fs::remove_dir(&kat_dir);
+    }
+
+    if cleaned == 0 {
+        println!("{}", "✨ There was nothing to clean!".bright_green());
+    } else {
+        println!(
+            "{}",
+            format!("✨ Removed the build files of {cleaned} problem(s)!").bright_green()
+        );
+    }
+
+    Ok(())
+}
+
+/// Whether the path is inside the directory, and not the directory itself
+fn is_strictly_inside(path: &Path, directory: &Path) -> bool {
+    path != directory && path.starts_with(directory)
+}
+
+/// Finds the problem directories in the workspace, i.e. directories with tests, a problem config or kat build files
+/// The workspace itself can have a .kat directory to hold the builds of its problems, so that does not make it a problem.
+fn find_problem_dirs(
+    path: &Path,
+    is_workspace: bool,
+    problem_paths: &mut Vec<PathBuf>,
+) -> Result<(), Report> {
+    let markers: &[&str] = if is_workspace {
+        &["tests", "kat.toml"]
+    } else {
+        &["tests", "kat.toml", ".kat"]
+    };
+    let is_problem_dir = markers.iter().any(|name| path.join(name).exists());
+    if is_problem_dir {
+        problem_paths.push(path.to_path_buf());
+        return Ok(());
+    }
+
+    let entries = fs::read_dir(path).wrap_err(format!(
+        "🙀 Failed to read the directory {}",
+        path.display()
+    ))?;
+    for entry in entries.filter_map(Result::ok) {
+        let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if !is_hidden && entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+            find_problem_dirs(&entry.path(), false, problem_paths)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use crate::{
+    cli::{BuildProfile, Debug, TestOptions},
+    commands::{
+        run::print_summary,
+        test::{build_command, compile_problem, execute_error, prepare_arguments, TestSettings},
+    },
+    utils::{find_problem_dir, find_test_files, get_problem_file, process::run_attached, TestCase},
+    App,
+};
+
+use std::{
+    fs::File,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use color_eyre::{
+    eyre::{self, Context},
+    Report,
+};
+
+/// Runs the solution on a single test with `kat debug`, under the wrapper given with `--wrap` or in the language config
+pub async fn debug(app: &App, args: &Debug) -> Result<(), Report> {
+    let (problem_path, problem_id) = find_problem_dir(app, &args.path)?;
+    let (problem_file, problem_file_path, language) =
+        get_problem_file(app, &args.file, &args.language, &problem_path, &problem_id)?;
+
+    let tests = find_test_files(app, &Some(args.test.clone()), &problem_path, true)?;
+    // `kat debug 1` means tests/1.in, even if there is also a tests/secret/1.in
+    let exact_matches: Vec<&TestCase> = tests
+        .iter()
+        .filter(|test| is_exact_match(test, &args.test))
+        .collect();
+    let test = match (exact_matches.as_slice(), tests.as_slice()) {
+        ([test], _) => *test,
+        (_, [test]) => test,
+        _ => eyre::bail!(
+            "🙀 {} matches {} tests, but only a single test can be debugged: {}",
+            args.test,
+            tests.len(),
+            tests
+                .iter()
+                .map(|test| test.name())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+
+    let options = TestOptions {
+        rebuild: args.rebuild,
+        profile: BuildProfile::Debug,
+        ..Default::default()
+    };
+    let settings = TestSettings::resolve(app, &language, &problem_path, &options)?;
+    if settings.interactor.is_some() {
+        eyre::bail!("🙀 Debugging the tests of interactive problems is not supported!");
+    }
+
+    let language_config = &app.config.kat_config.languages[&language];
+    if let Some(compile_command) = language_config.compile_command_for(settings.profile) {
+        compile_problem(
+            compile_command,
+            &problem_path,
+            &problem_file_path,
+            &format!("problem: {problem_id}"),
+            &settings,
+        )?;
+    }
+
+    let command = build_command(
+        &language_config.execute_command,
+        &settings.build_directory,
+        &problem_file_path,
+    )?;
+    let wrapper = args
+        .wrap
+        .as_ref()
+        .or(language_config.debug_wrapper.as_ref());
+    let (mut command, redirect_input) = match wrapper {
+        Some(wrapper) => wrap_command(
+            wrapper,
+            &command,
+            &problem_file_path,
+            &settings.build_directory,
+            &test.input,
+        )?,
+        None => {
+            log::info!("No wrapper was given with --wrap or as the debug_wrapper of {language}");
+            (command, true)
+        }
+    };
+
+    println!("🐞 Debugging {problem_file} on test {} ...", test.name());
+    let stdin = if redirect_input {
+        Stdio::from(File::open(&test.input).wrap_err(format!(
+            "🙀 Failed to open the input {}",
+            test.input.display()
+        ))?)
+    } else {
+        Stdio::inherit()
+    };
+    let output = run_attached(&mut command, stdin, &settings.limits)
+        .map_err(|e| execute_error(&command, e))?;
+    print_summary(&output);
+    Ok(())
+}
+
+/// Puts the wrapper in front of the command. The wrapper can use the same placeholders as the execute command,
+/// and `{input_file}` for the path of the test input. Returns the wrapped command, and whether the input should
+/// still be redirected to stdin, which is not the case if the wrapper takes care of it with `{input_file}`.
+fn wrap_command(
+    wrapper: &str,
+    command: &Command,
+    problem_file_path: &Path,
+    build_directory: &Path,
+    input_file: &Path,
+) -> Result<(Command, bool), Report> {
+    let input_file = input_file
+        .canonicalize()
+        .wrap_err("🙀 Failed to find the test input")?;
+    let redirect_input = !wrapper.contains("{input_file}");
+    let wrapper_args: Vec<String> = prepare_arguments(wrapper, problem_file_path, build_directory)?
+        .into_iter()
+        .map(|arg| arg.replace("{input_file}", input_file.to_str().unwrap()))
+        .collect();
+    let (wrapper_cmd, wrapper_args) = wrapper_args
+        .split_first()
+        .ok_or_else(|| eyre::eyre!("🙀 The debug wrapper is empty"))?;
+
+    let mut wrapped = Command::new(wrapper_cmd);
+    wrapped
+        .args(wrapper_args)
+        .arg(command.get_program())
+        .args(command.get_args());
+    Ok((wrapped, redirect_input))
+}
+
+/// Whether the test is exactly the one named, by its path in the tests folder with or without extension (e.g. `1` or `secret/1.in`)
+fn is_exact_match(test: &TestCase, name: &str) -> bool {
+    let name = name.trim();
+    let full_name = test.name();
+    let stem = full_name
+        .rsplit_once('.')
+        .map_or(full_name.as_str(), |(stem, _)| stem);
+    full_name == name || stem == name
+}
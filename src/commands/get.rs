@@ -1,9 +1,10 @@
 use crate::{
     cli::Get,
+    utils::config::ProblemConfig,
     utils::fileutils::{copy_template, get_problem_dir, get_test_dir},
     utils::{
         webutils::{
-            check_change_hostname, get_sample_url_from_problem_url, is_problem_id, problem_exists,
+            check_change_hostname, get_problem_page, get_sample_url_from_problem_url, is_problem_id,
         },
         HttpClient,
     },
@@ -13,11 +14,14 @@ use crate::{
 use std::{
     fs::{self},
     io::Write,
+    path::Path,
 };
 
 use color_eyre::{eyre, eyre::Context, Report};
 
 use colored::Colorize;
+use regex::Regex;
+use scraper::Html;
 
 pub async fn get(app: &App, args: &Get) -> Result<(), Report> {
     let problem = &args.problem;
@@ -33,9 +37,9 @@ pub async fn get(app: &App, args: &Get) -> Result<(), Report> {
 
     let http_client = HttpClient::new().unwrap();
 
-    if !problem_exists(&http_client, problem, hostname).await? {
+    let Some(problem_page) = get_problem_page(&http_client, problem, hostname).await? else {
         eyre::bail!("🙀 Problem {} does not exist!", problem);
-    }
+    };
 
     let problem_dir = get_problem_dir(app, problem)?;
     if problem_dir.exists() {
@@ -75,6 +79,8 @@ pub async fn get(app: &App, args: &Get) -> Result<(), Report> {
 
     fetch_tests(app, problem, &url, &http_client).await?;
 
+    save_problem_config(problem, &problem_dir, &problem_page);
+
     println!(
         "{}",
         format!("📝 Creating template file for problem {}...", problem).bright_blue()
@@ -141,3 +147,50 @@ async fn fetch_tests(
         }
     }
 }
+
+/// Saves the limits shown on the problem page in the problem config. The tests can be run without them,
+/// so the config is skipped with a warning if they can not be found.
+fn save_problem_config(problem: &str, problem_dir: &Path, problem_page: &str) {
+    // the limits are shown in the sidebar of the problem page, e.g., "CPU Time limit 1 second" and "Memory limit 1024 MB"
+    let document = Html::parse_document(problem_page);
+    let text = document.root_element().text().collect::<Vec<_>>().join(" ");
+
+    let re = Regex::new(r"CPU Time limit\s+(\d+(?:\.\d+)?)\s+second").unwrap();
+    let time_limit = re
+        .captures(&text)
+        .and_then(|captures| captures.get(1))
+        .and_then(|time_limit| time_limit.as_str().parse::<f64>().ok());
+
+    let re = Regex::new(r"Memory limit\s+(\d+)\s+MB").unwrap();
+    let memory_limit = re
+        .captures(&text)
+        .and_then(|captures| captures.get(1))
+        .and_then(|memory_limit| memory_limit.as_str().parse::<u64>().ok());
+
+    if time_limit.is_none() && memory_limit.is_none() {
+        log::warn!(
+            "{}",
+            format!(
+                "🙀 Could not find the limits of the problem {problem}, not creating kat.toml!"
+            )
+            .bright_yellow()
+        );
+        return;
+    }
+    if time_limit.is_none() || memory_limit.is_none() {
+        log::warn!(
+            "{}",
+            format!("🙀 Could not find the time and memory limits for the problem {problem}!")
+                .bright_yellow()
+        );
+    }
+
+    let problem_config = ProblemConfig {
+        time_limit,
+        memory_limit,
+        ..Default::default()
+    };
+    if let Err(e) = problem_config.save(problem_dir) {
+        log::warn!("{}", format!("{e}").bright_yellow());
+    }
+}
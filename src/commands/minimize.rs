@@ -0,0 +1,289 @@
+use crate::{
+    cli::Test,
+    commands::{
+        stress::run_reference,
+        test::{
+            compile_all, compile_helper_program, execute_problem, HelperProgram, OutputValidator,
+            TestResult, TestSettings,
+        },
+    },
+    utils::{
+        find_helper_program, find_test_files,
+        minimize::{minimize, shrinkers},
+        save_test,
+        verdict::Verdict,
+        TestCase,
+    },
+    App,
+};
+
+use std::{fs, path::Path, time::Duration};
+
+use color_eyre::{
+    eyre::{self, Context, ContextCompat},
+    Report,
+};
+use colored::Colorize;
+use indicatif::ProgressBar;
+
+/// Minimizes the input of a failing test with `kat test --minimize`, and saves the smallest input that fails as a new test
+pub fn minimize_test(
+    app: &App,
+    args: &Test,
+    problem_id: &str,
+    problem_path: &Path,
+    problem_file_path: &Path,
+    language: &str,
+    settings: &TestSettings,
+) -> Result<(), Report> {
+    let test_name = args.minimize.as_ref().wrap_err("🙀 No test to minimize")?;
+    // only the test that is minimized needs an answer, not the other tests of the problem
+    let test = find_test_files(app, &None, problem_path, true)?
+        .into_iter()
+        .find(|test| {
+            test.name() == *test_name
+                || test
+                    .input
+                    .file_stem()
+                    .is_some_and(|stem| stem.to_string_lossy() == *test_name)
+        })
+        .wrap_err(format!("🙀 Could not find the test {test_name}"))?;
+    if test.is_interaction() || settings.interactor.is_some() {
+        eyre::bail!("🙀 Minimizing the tests of interactive problems is not supported!");
+    }
+    if test.answer.is_none() {
+        eyre::bail!(
+            "🙀 The test {} does not have an answer file (.ans or .out), so it can not be minimized!",
+            test.name()
+        );
+    }
+
+    let config = &app.config.kat_config;
+    let compile_command = config.languages[language]
+        .compile_command_for(settings.profile)
+        .unwrap_or_default();
+    let execute_command = &config.languages[language].execute_command;
+    compile_all(
+        app,
+        problem_id,
+        problem_path,
+        problem_file_path,
+        compile_command,
+        settings,
+    )?;
+    let reference = match find_helper_program(app, problem_path, &["reference", "brute"]) {
+        Some(file_path) => {
+            let reference = HelperProgram::new(app, file_path, Vec::new())?;
+            compile_helper_program(
+                app,
+                &reference,
+                problem_path,
+                "reference solution",
+                settings,
+            )?;
+            Some(reference)
+        }
+        None => None,
+    };
+
+    let result = execute_problem(app, execute_command, problem_file_path, &test, settings)?;
+    let verdict = result.verdict;
+    let failure = failure_signature(&result);
+    if result.passed() {
+        eyre::bail!(
+            "🙀 The test {} passes, so there is nothing to minimize!",
+            test.name()
+        );
+    }
+    // a custom output validator can judge the smaller inputs on its own, e.g. with the answer of the original test
+    let uses_default_validator = matches!(settings.validator, OutputValidator::Default(_));
+    if verdict == Verdict::WrongAnswer && reference.is_none() && uses_default_validator {
+        eyre::bail!(
+            "🙀 Minimizing a wrong answer with the default output validator needs a reference solution (e.g. brute.py) in the problem folder, to know the answers of the smaller inputs!"
+        );
+    }
+
+    let input = fs::read_to_string(&test.input).wrap_err("🙀 Failed to read the test input")?;
+    println!(
+        "{}",
+        format!(
+            "✂️ Minimizing the test {} ({} lines, {} bytes), which fails with {} ...",
+            test.name(),
+            input.lines().count(),
+            input.len(),
+            verdict.name()
+        )
+        .bold()
+        .bright_blue()
+    );
+
+    let work_dir = tempfile::tempdir().wrap_err("🙀 Failed to create a temporary directory")?;
+    let candidate = TestCase {
+        input: work_dir
+            .path()
+            .join(test.input.file_name().unwrap_or_default()),
+        answer: Some(match &reference {
+            Some(_) => work_dir.path().join("reference.ans"),
+            None => test.answer.clone().wrap_err("🙀 The test has no answer")?,
+        }),
+        group: test.group.clone(),
+    };
+    let pb = ProgressBar::new_spinner();
+    pb.enable_steady_tick(Duration::from_millis(100));
+    let mut attempts = 0;
+    let minimized = minimize(input, &shrinkers(args.shrink), |smaller| {
+        attempts += 1;
+        pb.set_message(format!("{attempts} inputs tried"));
+        fs::write(&candidate.input, smaller).wrap_err("🙀 Failed to write the smaller input")?;
+        if let Some(reference) = &reference {
+            let output = run_reference(app, reference, &candidate.input, settings)?;
+            // inputs the reference solution can not handle are most likely not valid
+            if !output.status.success() {
+                return Ok(false);
+            }
+            fs::write(candidate.answer.as_ref().unwrap(), &output.stdout)
+                .wrap_err("🙀 Failed to write the reference answer")?;
+        }
+        let result = execute_problem(
+            app,
+            execute_command,
+            problem_file_path,
+            &candidate,
+            settings,
+        )?;
+        Ok(failure_signature(&result) == failure)
+    })?;
+    pb.finish_and_clear();
+
+    // run the smallest input once more, to show how it fails and to leave its answer in place
+    fs::write(&candidate.input, &minimized).wrap_err("🙀 Failed to write the smaller input")?;
+    if let Some(reference) = &reference {
+        let output = run_reference(app, reference, &candidate.input, settings)?;
+        fs::write(candidate.answer.as_ref().unwrap(), &output.stdout)
+            .wrap_err("🙀 Failed to write the reference answer")?;
+    }
+    let result = execute_problem(
+        app,
+        execute_command,
+        problem_file_path,
+        &candidate,
+        settings,
+    )?;
+    println!("{}", result.report);
+
+    let saved_test = save_test(problem_path, &candidate, test.group.as_deref())?;
+    println!(
+        "{}",
+        format!(
+            "💾 Saved the minimized input ({} lines, {} bytes) as the test {} after trying {attempts} inputs",
+            minimized.lines().count(),
+            minimized.len(),
+            saved_test.display()
+        )
+        .bright_green()
+    );
+    if reference.is_none() {
+        println!(
+            "{}",
+            format!(
+                "⚠️ There is no reference solution, so the answer of the new test is copied from {} and is probably not correct for the smaller input",
+                test.name()
+            )
+            .bright_yellow()
+        );
+    }
+    Ok(())
+}
+
+/// What makes two failures the same: the verdict, and for run time errors also how the program crashed,
+/// so that e.g. a failed assertion is not minimized into an empty input that crashes while reading it
+fn failure_signature(result: &TestResult) -> (Verdict, Option<String>) {
+    let crash = result
+        .output
+        .as_ref()
+        .filter(|_| result.verdict == Verdict::RunTimeError)
+        .map(|output| {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let last_line = stderr.lines().rev().find(|line| !line.trim().is_empty());
+            format!("{} {}", output.status, last_line.unwrap_or_default())
+        });
+    (result.verdict, crash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::process::ProcessOutput;
+
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    fn result(verdict: Verdict, status: i32, stderr: &str) -> TestResult {
+        TestResult {
+            verdict,
+            report: String::new(),
+            output: Some(ProcessOutput {
+                status: ExitStatus::from_raw(status),
+                stdout: Vec::new(),
+                stderr: stderr.as_bytes().to_vec(),
+                wall_time: Duration::ZERO,
+                user_time: Duration::ZERO,
+                system_time: Duration::ZERO,
+                peak_memory: 0,
+                timed_out: false,
+                exceeded_output_limit: false,
+            }),
+        }
+    }
+
+    #[test]
+    fn crashes_are_told_apart_by_how_the_program_crashed() {
+        let assertion = result(
+            Verdict::RunTimeError,
+            6,
+            "main: Assertion `x > 0' failed.\n\n",
+        );
+        let same_assertion = result(
+            Verdict::RunTimeError,
+            6,
+            "main: Assertion `x > 0' failed.\n",
+        );
+        let read_error = result(Verdict::RunTimeError, 256, "ValueError: empty input\n");
+
+        assert_eq!(
+            failure_signature(&assertion),
+            (
+                Verdict::RunTimeError,
+                Some("signal: 6 (SIGABRT) main: Assertion `x > 0' failed.".to_string())
+            )
+        );
+        assert_eq!(
+            failure_signature(&assertion),
+            failure_signature(&same_assertion)
+        );
+        assert_ne!(
+            failure_signature(&assertion),
+            failure_signature(&read_error)
+        );
+    }
+
+    #[test]
+    fn other_failures_are_told_apart_by_verdict() {
+        let wrong_answer = result(Verdict::WrongAnswer, 0, "debug output\n");
+        let other_wrong_answer = result(Verdict::WrongAnswer, 0, "other debug output\n");
+        let time_limit = result(Verdict::TimeLimitExceeded, 0, "");
+
+        assert_eq!(
+            failure_signature(&wrong_answer),
+            (Verdict::WrongAnswer, None)
+        );
+        assert_eq!(
+            failure_signature(&wrong_answer),
+            failure_signature(&other_wrong_answer)
+        );
+        assert_ne!(
+            failure_signature(&wrong_answer),
+            failure_signature(&time_limit)
+        );
+    }
+}
@@ -1,15 +1,28 @@
+mod add_test;
+mod all_solutions;
+mod bench;
+mod clean;
 mod config;
+mod debug;
 mod get;
 mod init;
+mod minimize;
 mod open;
+mod run;
+mod stress;
 mod submit;
 mod test;
 mod watch;
 
+pub use bench::bench;
+pub use clean::clean;
 pub use config::config;
+pub use debug::debug;
 pub use get::get;
 pub use init::init;
 pub use open::open;
+pub use run::run;
+pub use stress::stress;
 pub use submit::submit;
 pub use test::test;
 pub use watch::watch;
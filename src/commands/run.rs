@@ -0,0 +1,95 @@
+use crate::{
+    cli::{Run, TestOptions},
+    commands::test::{build_command, compile_problem, execute_error, format_usage, TestSettings},
+    utils::{
+        find_problem_dir, get_problem_file,
+        process::{run_attached, ProcessOutput},
+        verdict::{describe_crash, Verdict},
+    },
+    App,
+};
+
+use std::{
+    fs::File,
+    io::{self, IsTerminal},
+    process::Stdio,
+};
+
+use color_eyre::{eyre::Context, Report};
+use colored::Colorize;
+
+pub async fn run(app: &App, args: &Run) -> Result<(), Report> {
+    let (problem_path, problem_id) = find_problem_dir(app, &args.path)?;
+    let (problem_file, problem_file_path, language) =
+        get_problem_file(app, &args.file, &args.language, &problem_path, &problem_id)?;
+    let options = TestOptions {
+        rebuild: args.rebuild,
+        ..Default::default()
+    };
+    let mut settings = TestSettings::resolve(app, &language, &problem_path, &options)?;
+    // only the output of the program should end up in e.g. a file the output is redirected to
+    settings.quiet = !io::stdout().is_terminal();
+
+    let language_config = &app.config.kat_config.languages[&language];
+    if let Some(compile_command) = language_config.compile_command_for(settings.profile) {
+        compile_problem(
+            compile_command,
+            &problem_path,
+            &problem_file_path,
+            &format!("problem: {problem_id}"),
+            &settings,
+        )?;
+    }
+    let mut command = build_command(
+        &language_config.execute_command,
+        &settings.build_directory,
+        &problem_file_path,
+    )?;
+
+    let stdin = match &args.input {
+        Some(input) => Stdio::from(
+            File::open(input)
+                .wrap_err(format!("🙀 Failed to open the input {}", input.display()))?,
+        ),
+        None => {
+            if io::stdin().is_terminal() {
+                eprintln!("⌨️ Type the input of {problem_file}, and end it with Ctrl+D:");
+            }
+            Stdio::inherit()
+        }
+    };
+    let output = run_attached(&mut command, stdin, &settings.limits)
+        .map_err(|e| execute_error(&command, e))?;
+
+    print_summary(&output);
+    Ok(())
+}
+
+/// Prints how the program finished, and the likely cause if it crashed.
+/// This goes to stderr, so that it does not end up where the output of the program is redirected to.
+pub fn print_summary(output: &ProcessOutput) {
+    let usage = format!(
+        "in {:.2}s! {}",
+        output.wall_time.as_secs_f64(),
+        format_usage(output)
+    );
+    if output.status.success() {
+        eprintln!(
+            "{}",
+            format!("\n✅ The program finished with exit code 0 {usage}").bright_green()
+        );
+    } else {
+        // the error output was shown directly, so the crash can only be decoded from the exit status
+        let crash = describe_crash(&output.status, "");
+        eprintln!(
+            "{}\n{}",
+            format!(
+                "\n{} The program failed with {} {usage}",
+                Verdict::RunTimeError.emoji(),
+                crash.reason
+            )
+            .bright_red(),
+            format!("💡 {}", crash.hint).bright_yellow()
+        );
+    }
+}
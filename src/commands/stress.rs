@@ -0,0 +1,236 @@
+use crate::{
+    cli::{Stress, TestOptions},
+    commands::test::{
+        compile_all, compile_helper_program, execute_error, execute_problem, helper_limits,
+        HelperProgram, TestSettings,
+    },
+    utils::{
+        errors::KatError,
+        find_helper_program, find_problem_dir, get_problem_file,
+        process::{run_limited, ProcessOutput},
+        save_test, TestCase,
+    },
+    App,
+};
+
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::{
+    eyre::{self, Context},
+    Report,
+};
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+
+pub async fn stress(app: &App, args: &Stress) -> Result<(), Report> {
+    let (problem_path, problem_id) = find_problem_dir(app, &args.path)?;
+    let (problem_file, problem_file_path, language) =
+        get_problem_file(app, &args.file, &args.language, &problem_path, &problem_id)?;
+    let options = TestOptions {
+        time_limit: args.time_limit,
+        validator_flags: args.validator_flags.clone(),
+        rebuild: args.rebuild,
+        ..Default::default()
+    };
+    let settings = TestSettings::resolve(app, &language, &problem_path, &options)?;
+    if settings.interactor.is_some() {
+        eyre::bail!("🙀 Stress testing interactive problems is not supported!");
+    }
+
+    let generator = find_program(
+        app,
+        &problem_path,
+        &args.generator,
+        "generator",
+        &["generator", "gen"],
+    )?;
+    let reference = find_program(
+        app,
+        &problem_path,
+        &args.reference,
+        "reference solution",
+        &["reference", "brute"],
+    )?;
+
+    println!(
+        "{}",
+        format!(
+            "💪 Stress testing problem: {} with the file {} against {} ...\n",
+            problem_id,
+            &problem_file,
+            reference.file_name()
+        )
+        .bold()
+        .bright_blue()
+    );
+
+    let config = &app.config.kat_config;
+    let compile_command = config.languages[&language]
+        .compile_command_for(settings.profile)
+        .unwrap_or_default();
+    let execute_command = &config.languages[&language].execute_command;
+    compile_all(
+        app,
+        &problem_id,
+        &problem_path,
+        &problem_file_path,
+        compile_command,
+        &settings,
+    )?;
+    compile_helper_program(app, &generator, &problem_path, "generator", &settings)?;
+    compile_helper_program(
+        app,
+        &reference,
+        &problem_path,
+        "reference solution",
+        &settings,
+    )?;
+
+    // the seed only has to differ between runs, so the current time is random enough
+    let first_seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64 % 1_000_000_000)
+    });
+    let work_dir = tempfile::tempdir().wrap_err("🙀 Failed to create a temporary directory")?;
+
+    let pb = ProgressBar::new(args.iterations);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{bar:30} {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+
+    for seed in (first_seed..).take(args.iterations as usize) {
+        pb.set_message(format!("seed {seed}"));
+        let test = generate_test(
+            app,
+            &generator,
+            &reference,
+            seed,
+            work_dir.path(),
+            &settings,
+        )?;
+        let result = execute_problem(app, execute_command, &problem_file_path, &test, &settings)?;
+        if !result.passed() {
+            pb.finish_and_clear();
+            println!("{}", result.report);
+            let saved_test = save_test(&problem_path, &test, None)?;
+            println!(
+                "{}",
+                format!(
+                    "💾 Found a counterexample with seed {seed}, and saved it as the test {}",
+                    saved_test.display()
+                )
+                .bright_red()
+            );
+            return Err(KatError::TestsFailed.into());
+        }
+        let _ = fs::remove_file(&test.input);
+        let _ = test.answer.as_ref().map(fs::remove_file);
+        pb.inc(1);
+    }
+    pb.finish_and_clear();
+
+    println!(
+        "{}",
+        format!(
+            "🏁 The solution agreed with the reference solution on all {} inputs (seeds {} to {})!",
+            args.iterations,
+            first_seed,
+            first_seed + args.iterations.saturating_sub(1)
+        )
+        .bright_green()
+    );
+    Ok(())
+}
+
+/// Finds the generator or reference solution, either from the command line or by its name in the problem directory
+pub fn find_program(
+    app: &App,
+    problem_path: &Path,
+    file: &Option<PathBuf>,
+    kind: &str,
+    names: &[&str],
+) -> Result<HelperProgram, Report> {
+    let file_path = match file {
+        Some(file) => {
+            if !file.exists() {
+                eyre::bail!("🙀 The {kind} {} does not exist!", file.display());
+            }
+            file.clone()
+        }
+        None => find_helper_program(app, problem_path, names).ok_or_else(|| {
+            eyre::eyre!(
+                "🙀 Could not find a {kind} in {} - name it e.g. {}.py or specify it with --{}",
+                problem_path.display(),
+                names[0],
+                names[0]
+            )
+        })?,
+    };
+    HelperProgram::new(app, file_path, Vec::new())
+}
+
+/// Runs the generator with the given seed to create an input, and the reference solution on it to create the answer
+fn generate_test(
+    app: &App,
+    generator: &HelperProgram,
+    reference: &HelperProgram,
+    seed: u64,
+    work_dir: &Path,
+    settings: &TestSettings,
+) -> Result<TestCase, Report> {
+    let input = work_dir.join(format!("seed-{seed}.in"));
+    let answer = work_dir.join(format!("seed-{seed}.ans"));
+
+    let mut command = generator.execute_command(app, &settings.build_directory)?;
+    command.arg(seed.to_string());
+    let output = run_limited(&mut command, Stdio::null(), &helper_limits())
+        .map_err(|e| execute_error(&command, e))?;
+    if !output.status.success() {
+        eyre::bail!(
+            "🙀 The generator {} failed with {} for seed {seed}\n{}",
+            generator.file_name(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    fs::write(&input, &output.stdout).wrap_err("🙀 Failed to write the generated input")?;
+
+    let output = run_reference(app, reference, &input, settings)?;
+    if !output.status.success() {
+        eyre::bail!(
+            "🙀 The reference solution {} failed with {} on the input of seed {seed}\n{}",
+            reference.file_name(),
+            output.status,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    fs::write(&answer, &output.stdout).wrap_err("🙀 Failed to write the reference answer")?;
+
+    Ok(TestCase {
+        input,
+        answer: Some(answer),
+        group: None,
+    })
+}
+
+/// Runs the reference solution on an input, whose output is the answer to the input if it exits successfully
+pub fn run_reference(
+    app: &App,
+    reference: &HelperProgram,
+    input: &Path,
+    settings: &TestSettings,
+) -> Result<ProcessOutput, Report> {
+    let mut command = reference.execute_command(app, &settings.build_directory)?;
+    let input_file = File::open(input)?;
+    run_limited(&mut command, Stdio::from(input_file), &helper_limits())
+        .map_err(|e| execute_error(&command, e))
+}
@@ -1,10 +1,10 @@
 use crate::{
-    cli::Submit,
-    commands::test::test_problem,
+    cli::{Submit, TestOptions},
+    commands::test::{test_problem, TestSettings},
     utils::{
         check_change_hostname, find_problem_dir, find_test_files, get_problem_file,
         get_submissions_url_from_hostname, get_submit_url_from_hostname, problem_exists,
-        HttpClient,
+        verdict::Verdict, HttpClient,
     },
     App,
 };
@@ -92,17 +92,11 @@ impl SubmissionStatus {
                 _ => "",
             },
             Self::Running => "🏃",
-            Self::Accepted => "✅",
-            Self::Failed(status) => match status.as_str() {
-                "Run Time Error" => "💥",
-                "Time Limit Exceeded" => "⌛️",
-                "Compile Error" => "🆘",
-                "Memory Limit Exceeded" => "🧠",
-                "Output Limit Exceeded" => "🌊",
-                "Wrong Answer" => "💔",
-                "Judge Error" => "🔮",
-                _ => "",
-            },
+            Self::Accepted => Verdict::Accepted.emoji(),
+            // the same emojis are used for the verdicts of local tests
+            Self::Failed(status) => {
+                Verdict::from_kattis(status).map_or("", |verdict| verdict.emoji())
+            }
             Self::Unknown => "",
         }
     }
@@ -143,7 +137,12 @@ pub async fn submit(app: &App, args: &Submit) -> Result<(), Report> {
 
     if problem_file_path.exists() {
         if args.test_first {
-            let tests = find_test_files(app, &Some("all".to_string()), &problem_path)?;
+            let tests = find_test_files(app, &Some("all".to_string()), &problem_path, false)?;
+            let options = TestOptions {
+                report: args.report.clone(),
+                ..Default::default()
+            };
+            let settings = TestSettings::resolve(app, &language, &problem_path, &options)?;
             println!(
                 "{}",
                 format!(
@@ -152,14 +151,16 @@ pub async fn submit(app: &App, args: &Submit) -> Result<(), Report> {
                 )
                 .bright_blue()
             );
-            if !test_problem(
+            let run = test_problem(
                 app,
                 &problem_id,
                 &problem_path,
                 &problem_file_path,
                 tests,
                 &language,
-            )? {
+                &settings,
+            )?;
+            if !run.passed {
                 eyre::bail!("❌ Some tests seem to have failed, aborting submission!");
             }
         }
@@ -453,6 +454,9 @@ async fn parse_submission_data(
     };
 
     let td_selector = Selector::parse("td[data-type]").unwrap();
+    let problem_re = Regex::new(r#"<a href=".*">(.*)</a>"#).unwrap();
+    let status_re = Regex::new(r"<span>(.*)</span>").unwrap();
+    let testcases_re = Regex::new(r#"<div class="horizontal_item">(.*)</div>"#).unwrap();
     let tds = data_row.select(&td_selector);
     for td in tds {
         let data_type = td.value().attr("data-type").unwrap();
@@ -461,8 +465,7 @@ async fn parse_submission_data(
             "plagiarism" => submission_data.plagiarism = data_value,
             "time" => submission_data.time = data_value,
             "problem" => {
-                let re = Regex::new(r#"<a href=".*">(.*)</a>"#).unwrap();
-                submission_data.problem = re
+                submission_data.problem = problem_re
                     .captures(&data_value)
                     .wrap_err("🙀 Could not find problem name from submission table")?
                     .get(1)
@@ -471,8 +474,7 @@ async fn parse_submission_data(
                     .to_string();
             }
             "status" => {
-                let re = Regex::new(r"<span>(.*)</span>").unwrap();
-                submission_data.status = re
+                submission_data.status = status_re
                     .captures(&data_value)
                     .wrap_err("🙀 Could not find submission status from submission table")?
                     .get(1)
@@ -483,8 +485,7 @@ async fn parse_submission_data(
             "cpu" => submission_data.cpu = data_value.replace("&nbsp;", " "),
             "lang" => submission_data.lang = data_value,
             "testcases" => {
-                let re = Regex::new(r#"<div class="horizontal_item">(.*)</div>"#).unwrap();
-                submission_data.testcases = re
+                submission_data.testcases = testcases_re
                     .captures(&data_value)
                     .wrap_err("🙀 Could not find test cases from submission table")?
                     .get(1)
@@ -497,9 +498,9 @@ async fn parse_submission_data(
     }
 
     let tests = judge_table.select(&tests_selector);
+    let re = Regex::new(r"Test case (\d+)/\d+: (.*)").unwrap();
     for test in tests {
         let title = test.value().attr("title").unwrap().to_string();
-        let re = Regex::new(r"Test case (\d+)/\d+: (.*)").unwrap();
         let captures = re
             .captures(&title)
             .wrap_err("🙀 Could not find test title element")?;
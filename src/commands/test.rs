@@ -1,15 +1,45 @@
 use crate::{
-    cli::Test,
-    commands::submit::{send_submission, Submission},
-    utils::{find_problem_dir, find_test_files, get_problem_file, HttpClient},
+    cli::{
+        BuildProfile, OutputFormat, ReportFormat, ReportOption, Test, TestCommands, TestOptions,
+    },
+    commands::{
+        add_test::add_test,
+        all_solutions::test_all_solutions,
+        minimize::minimize_test,
+        submit::{send_submission, Submission},
+    },
+    utils::{
+        build_cache::BuildStamp,
+        choose_language_by_extension,
+        config::ProblemConfig,
+        diff::format_diff,
+        errors::KatError,
+        find_build_directory, find_output_validator, find_problem_dir, find_test_files,
+        get_problem_file,
+        interaction::{format_transcript, play_interaction},
+        junit::{write_junit_report, JunitCase, JunitOutcome, JunitSuite},
+        pager::print_report,
+        process::{run_interactive, run_limited, spawn_limited, Limits, ProcessOutput},
+        read_failed_tests, update_failed_tests,
+        validator::{validate_output, Judgement, ValidatorFlags},
+        verdict::{describe_crash, sanitizer_report, Verdict},
+        HttpClient, TestCase,
+    },
     App,
 };
 
 use std::{
+    collections::BTreeMap,
     fs::{self, File},
-    io,
+    io::{self, Seek, SeekFrom, Write},
     path::{Path, PathBuf},
     process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::Duration,
 };
 
 use color_eyre::{
@@ -18,33 +48,96 @@ use color_eyre::{
 };
 
 use colored::Colorize;
+use serde::Serialize;
+
+// The time limit used when neither the command line nor the problem config specifies one
+const DEFAULT_TIME_LIMIT: f64 = 5.0;
+// The memory limit (in megabytes) used when neither the command line nor the problem config specifies one,
+// this is the same as the default memory limit on Kattis
+const DEFAULT_MEMORY_LIMIT: u64 = 1024;
+// The output limit (in megabytes) used when the language does not specify one, the default of Kattis problem packages
+const DEFAULT_OUTPUT_LIMIT: u64 = 8;
+// The subdirectory of the build directory where the builds of the debug profile are placed
+const DEBUG_BUILD_DIRECTORY: &str = ".debug";
+// The directory in the problem directory where the output of each test is saved with --save-output
+const OUTPUT_DIRECTORY: &str = ".kat/output";
+// Helper programs like output validators and interactors are trusted, so they just get a generous time limit to make sure they terminate
+const HELPER_TIME_LIMIT: Duration = Duration::from_secs(60);
 
 pub async fn test(app: &App, args: &Test) -> Result<(), Report> {
+    if let Some(TestCommands::Add(add_args)) = &args.command {
+        return add_test(app, add_args);
+    }
     let (problem_path, problem_id) = find_problem_dir(app, &args.path)?;
+    if args.minimize.is_some() {
+        let (_, problem_file_path, language) =
+            get_problem_file(app, &args.file, &args.language, &problem_path, &problem_id)?;
+        let settings = TestSettings::resolve(app, &language, &problem_path, &args.options)?;
+        return minimize_test(
+            app,
+            args,
+            &problem_id,
+            &problem_path,
+            &problem_file_path,
+            &language,
+            &settings,
+        );
+    }
+    let mut tests = find_test_files(app, &args.test_cases, &problem_path, args.options.bless)?;
+    if args.failed {
+        let failed_tests = read_failed_tests(&problem_path);
+        tests.retain(|test| failed_tests.contains(&test.name()));
+        if tests.is_empty() {
+            println!(
+                "{}",
+                format!("🏁 No tests failed the last time {problem_id} was tested!").bright_green()
+            );
+            return Ok(());
+        }
+    }
+    if args.all_solutions {
+        return test_all_solutions(app, args, &problem_id, &problem_path, tests);
+    }
     let (problem_file, problem_file_path, language) =
         get_problem_file(app, &args.file, &args.language, &problem_path, &problem_id)?;
-    let tests = find_test_files(app, &args.test_cases, &problem_path)?;
+    let mut settings = TestSettings::resolve(app, &language, &problem_path, &args.options)?;
+    let json = args.format == OutputFormat::Json;
+    settings.quiet = json;
 
-    println!(
-        "{}",
-        format!(
-            "🧪 Testing problem: {} with the file {} ...\n",
-            problem_id, &problem_file
-        )
-        .bold()
-        .bright_blue()
-    );
+    if !json {
+        println!(
+            "{}",
+            format!(
+                "🧪 Testing problem: {} with the file {} ...\n",
+                problem_id, &problem_file
+            )
+            .bold()
+            .bright_blue()
+        );
+    }
 
-    let passed = test_problem(
+    let run = match test_problem(
         app,
         &problem_id,
         &problem_path,
         &problem_file_path,
         tests,
         &language,
-    )?;
-
-    if passed {
+        &settings,
+    ) {
+        Ok(run) => run,
+        Err(e) => {
+            // the summary is also printed when the problem fails to compile, so that scripts can tell what happened
+            if let Some(KatError::CompileError { message, .. }) = e.downcast_ref().filter(|_| json)
+            {
+                print_json_summary(&problem_id, &problem_file, None, Some(message))?;
+            }
+            return Err(e);
+        }
+    };
+    if json {
+        print_json_summary(&problem_id, &problem_file, Some(&run), None)?;
+    } else if run.passed {
         if args.submit {
             let submit = dialoguer::Select::new()
                 .with_prompt("Do you want to submit this file?")
@@ -82,6 +175,88 @@ pub async fn test(app: &App, args: &Test) -> Result<(), Report> {
         );
     }
 
+    if run.passed {
+        Ok(())
+    } else {
+        Err(KatError::TestsFailed.into())
+    }
+}
+
+/// The summary printed by `kat test --format json`
+#[derive(Debug, Serialize)]
+struct JsonSummary<'a> {
+    problem: &'a str,
+    file: &'a str,
+    /// Either "passed", "failed" or "compile_error"
+    status: &'a str,
+    /// The error message if the problem failed to compile
+    error: Option<&'a str>,
+    passed: usize,
+    total: usize,
+    /// The time it took to run all of the tests, in seconds
+    time: f64,
+    tests: Vec<JsonTestResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonTestResult {
+    name: String,
+    verdict: Verdict,
+    /// The wall-clock time of the test in seconds, if the program was run
+    time: Option<f64>,
+    /// The CPU time (user and system) of the test in seconds, if the program was run
+    cpu_time: Option<f64>,
+    /// The peak memory usage of the test in megabytes, if the program was run
+    memory: Option<f64>,
+}
+
+fn print_json_summary(
+    problem_id: &str,
+    problem_file: &str,
+    run: Option<&TestRun>,
+    compile_error: Option<&str>,
+) -> Result<(), Report> {
+    let tests: Vec<JsonTestResult> = run
+        .map(|run| {
+            run.results
+                .iter()
+                .map(|(test, result)| JsonTestResult {
+                    name: test.name(),
+                    verdict: result.verdict,
+                    time: result.output.as_ref().map(|o| o.wall_time.as_secs_f64()),
+                    cpu_time: result.output.as_ref().map(|o| o.cpu_time().as_secs_f64()),
+                    memory: result
+                        .output
+                        .as_ref()
+                        .map(|o| o.peak_memory as f64 / (1024.0 * 1024.0)),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let status = match run {
+        Some(run) if run.passed => "passed",
+        Some(_) => "failed",
+        None => "compile_error",
+    };
+    let summary = JsonSummary {
+        problem: problem_id,
+        file: problem_file,
+        status,
+        error: compile_error,
+        passed: tests
+            .iter()
+            .filter(|test| test.verdict == Verdict::Accepted)
+            .count(),
+        total: tests.len(),
+        time: run.map_or(0.0, |run| run.elapsed.as_secs_f64()),
+        tests,
+    };
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&summary).wrap_err("🙀 Failed to serialize the summary")?
+    );
     Ok(())
 }
 
@@ -90,39 +265,88 @@ pub fn test_problem(
     problem_id: &str,
     problem_path: &Path,
     problem_file_path: &Path,
-    tests: Vec<(PathBuf, PathBuf)>,
+    tests: Vec<TestCase>,
     language: &str,
-) -> Result<bool, Report> {
+    settings: &TestSettings,
+) -> Result<TestRun, Report> {
     let config = &app.config.kat_config;
 
-    let compile_command = match &config.languages.get(language).unwrap().compile_command {
-        Some(compile_command) => compile_command,
-        None => "",
-    };
+    let compile_command = config.languages[language]
+        .compile_command_for(settings.profile)
+        .unwrap_or_default();
 
     let execute_command = &config.languages.get(language).unwrap().execute_command;
 
-    if !compile_command.is_empty() {
-        println!("🔨 Compiling problem: {} ...", problem_id);
-        compile_problem(compile_command, problem_path, problem_file_path)?;
+    let compiled = compile_all(
+        app,
+        problem_id,
+        problem_path,
+        problem_file_path,
+        compile_command,
+        settings,
+    );
+    if let Err(e) = compiled {
+        // the report should still tell that something went wrong, e.g. in CI
+        if let Some(report) = &settings.report {
+            let (message, output) = match e.downcast_ref() {
+                Some(KatError::CompileError { message, output }) => {
+                    (message.clone(), output.clone())
+                }
+                _ => (e.to_string(), String::new()),
+            };
+            let suite = JunitSuite {
+                name: problem_id.to_string(),
+                time: 0.0,
+                cases: vec![JunitCase {
+                    name: "compile".to_string(),
+                    time: 0.0,
+                    outcome: JunitOutcome::Error {
+                        message,
+                        details: output,
+                    },
+                }],
+            };
+            write_report(report, problem_id, &suite, settings)?;
+        }
+        return Err(e);
     }
 
-    let mut all_tests_passed = true;
     let start_time = std::time::Instant::now();
-    for single_test in tests {
-        if let Err(e) = execute_problem(
-            app,
-            execute_command,
-            problem_path,
-            problem_file_path,
-            single_test,
-        ) {
-            all_tests_passed = false;
-            println!("{e}");
+    let results = run_tests(app, execute_command, problem_file_path, &tests, settings);
+    let elapsed = start_time.elapsed();
+    let skipped_tests = tests.len() - results.len();
+    let run = TestRun {
+        passed: results.iter().all(TestResult::passed),
+        elapsed,
+        results: tests.into_iter().zip(results).collect(),
+    };
+    let (mut passed_tests, mut failed_tests) = (Vec::new(), Vec::new());
+    for (test, result) in &run.results {
+        if result.passed() {
+            passed_tests.push(test.name());
+        } else {
+            failed_tests.push(test.name());
         }
     }
-    let elapsed_time = format!("{:.2}", start_time.elapsed().as_secs_f64());
-    if all_tests_passed {
+    if settings.remember_failed_tests {
+        update_failed_tests(problem_path, &passed_tests, &failed_tests)?;
+    }
+    if let Some(report) = &settings.report {
+        write_report(report, problem_id, &run.to_junit(problem_id), settings)?;
+    }
+    if settings.quiet {
+        return Ok(run);
+    }
+
+    if skipped_tests > 0 {
+        println!(
+            "{}",
+            format!("⏭️ Skipped the last {skipped_tests} tests after the first failure")
+                .bright_yellow()
+        );
+    }
+    let elapsed_time = format!("{:.2}", elapsed.as_secs_f64());
+    if run.passed {
         println!(
             "{}",
             format!(
@@ -132,17 +356,600 @@ pub fn test_problem(
             )
             .bright_green()
         );
+    } else {
+        println!(
+            "{}",
+            format!(
+                "{} of {} tests for {} failed in {}s!",
+                run.failed_tests(),
+                run.results.len(),
+                problem_id.underline(),
+                elapsed_time
+            )
+            .bright_red()
+        );
+    }
+
+    let jobs = settings.jobs.min(run.results.len());
+    if jobs > 1
+        && run
+            .results
+            .iter()
+            .any(|(_, result)| result.near_time_limit(&settings.limits))
+    {
+        println!(
+            "{}",
+            format!(
+                "⚠️ Some tests ran close to (or over) the time limit while {} tests were run in parallel, which can affect their timing - try running them again with --jobs 1 to be sure!",
+                jobs
+            )
+            .bright_yellow()
+        );
+    }
+
+    Ok(run)
+}
+
+/// The outcome of testing a problem against all of its tests
+#[derive(Debug)]
+pub struct TestRun {
+    pub passed: bool,
+    /// The time it took to run all of the tests
+    pub elapsed: Duration,
+    /// The result of each test, in the order of the tests
+    pub results: Vec<(TestCase, TestResult)>,
+}
+
+impl TestRun {
+    pub fn failed_tests(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, result)| !result.passed())
+            .count()
+    }
+
+    fn to_junit(&self, problem_id: &str) -> JunitSuite {
+        let cases = self
+            .results
+            .iter()
+            .map(|(test, result)| {
+                let details = result.report.clone();
+                let outcome = match result.verdict {
+                    Verdict::Accepted => JunitOutcome::Passed,
+                    Verdict::JudgeError => JunitOutcome::Error {
+                        message: result.verdict.name().to_string(),
+                        details,
+                    },
+                    verdict => JunitOutcome::Failure {
+                        kind: verdict.name().to_string(),
+                        message: verdict.name().to_string(),
+                        details,
+                    },
+                };
+                JunitCase {
+                    name: test.name(),
+                    time: result
+                        .output
+                        .as_ref()
+                        .map_or(0.0, |output| output.wall_time.as_secs_f64()),
+                    outcome,
+                }
+            })
+            .collect();
+
+        JunitSuite {
+            name: problem_id.to_string(),
+            time: self.elapsed.as_secs_f64(),
+            cases,
+        }
+    }
+}
+
+/// Writes a report of the test results in the format asked for with `--report`
+fn write_report(
+    report: &ReportOption,
+    problem_id: &str,
+    suite: &JunitSuite,
+    settings: &TestSettings,
+) -> Result<(), Report> {
+    let path = PathBuf::from(
+        report
+            .path
+            .to_string_lossy()
+            .replace("{problem}", problem_id),
+    );
+    match report.format {
+        ReportFormat::Junit => write_junit_report(&path, suite)?,
+    }
+    if !settings.quiet {
+        println!("📝 Saved the test report to {}", path.display());
+    }
+    Ok(())
+}
+
+/// The result of running a single test case
+#[derive(Debug)]
+pub struct TestResult {
+    pub verdict: Verdict,
+    /// The report that is printed for the test
+    pub report: String,
+    /// The output and resource usage of the program, if it was run
+    pub output: Option<ProcessOutput>,
+}
+
+impl TestResult {
+    fn accepted(test_name: &str, output: ProcessOutput) -> Self {
+        let report = format!(
+            "{} Test {} passed in {:.2}s! {}",
+            Verdict::Accepted.emoji(),
+            test_name,
+            output.wall_time.as_secs_f64(),
+            format_usage(&output)
+        )
+        .bright_green()
+        .to_string();
+        TestResult {
+            verdict: Verdict::Accepted,
+            report,
+            output: Some(output),
+        }
+    }
+
+    /// A test whose answer was replaced by the output of the program with `--bless`
+    fn blessed(test_name: &str, output: ProcessOutput) -> Self {
+        let report = format!(
+            "✍️ Saved the output of test {} as its answer! {}",
+            test_name,
+            format_usage(&output)
+        )
+        .bright_yellow()
+        .to_string();
+        TestResult {
+            verdict: Verdict::Accepted,
+            report,
+            output: Some(output),
+        }
+    }
+
+    fn failed(verdict: Verdict, report: String, output: Option<ProcessOutput>) -> Self {
+        TestResult {
+            verdict,
+            report,
+            output,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.verdict == Verdict::Accepted
+    }
+
+    /// Whether the program ran for more than half of the time limit (in wall-clock time),
+    /// which means that the verdict might be affected by running tests in parallel
+    fn near_time_limit(&self, limits: &Limits) -> bool {
+        self.output
+            .as_ref()
+            .is_some_and(|output| output.timed_out || output.wall_time * 2 > limits.time_limit)
+    }
+}
+
+/// Runs the tests on `settings.jobs` threads, and prints the report of each test in the order of the tests.
+/// With `--pager`, the reports of the failed tests are printed after all tests have run instead.
+fn run_tests(
+    app: &App,
+    execute_command: &str,
+    problem_file_path: &Path,
+    tests: &[TestCase],
+    settings: &TestSettings,
+) -> Vec<TestResult> {
+    let jobs = settings.jobs.clamp(1, tests.len().max(1));
+    let next_test = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+    let (tx, rx) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let tx = tx.clone();
+            let next_test = &next_test;
+            let stop = &stop;
+            // tests are started in order, so the tests that were run are always the first ones
+            scope.spawn(move || loop {
+                if stop.load(Ordering::SeqCst) {
+                    break;
+                }
+                let index = next_test.fetch_add(1, Ordering::SeqCst);
+                let Some(test) = tests.get(index) else {
+                    break;
+                };
+                let result = run_test(app, execute_command, problem_file_path, test, settings);
+                if settings.fail_fast && !result.passed() {
+                    stop.store(true, Ordering::SeqCst);
+                }
+                if tx.send((index, result)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(tx);
+
+        // the tests can finish in any order, so hold on to the results until the previous ones are printed
+        let mut results = Vec::with_capacity(tests.len());
+        let mut pending = BTreeMap::new();
+        // with --pager, the reports of the failed tests are shown together in a single pager after the run
+        let mut failure_reports = Vec::new();
+        for (index, result) in rx {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&results.len()) {
+                if !settings.quiet {
+                    if settings.pager && !result.passed() {
+                        failure_reports.push(result.report.clone());
+                    } else {
+                        println!("{}", result.report);
+                    }
+                }
+                results.push(result);
+            }
+        }
+        if !failure_reports.is_empty() {
+            print_report(&failure_reports.join("\n"), settings.pager);
+        }
+        results
+    })
+}
+
+fn run_test(
+    app: &App,
+    execute_command: &str,
+    problem_file_path: &Path,
+    test: &TestCase,
+    settings: &TestSettings,
+) -> TestResult {
+    let result = if test.is_interaction() {
+        replay_interaction(execute_command, problem_file_path, test, settings)
+    } else if let Some(interactor) = &settings.interactor {
+        execute_interactive(
+            app,
+            execute_command,
+            problem_file_path,
+            test,
+            interactor,
+            settings,
+        )
+    } else {
+        execute_problem(app, execute_command, problem_file_path, test, settings)
+    };
+    result.unwrap_or_else(|e| TestResult::failed(Verdict::JudgeError, e.to_string(), None))
+}
+
+#[derive(Debug, Clone)]
+pub struct TestSettings {
+    pub limits: Limits,
+    pub validator: OutputValidator,
+    pub interactor: Option<HelperProgram>,
+    pub show_transcript: bool,
+    /// The number of tests to run in parallel
+    pub jobs: usize,
+    /// Whether to send long test reports through a pager
+    pub pager: bool,
+    /// Whether to compile the problem even if it has not changed since the last build
+    pub rebuild: bool,
+    /// The directory where the compiled files are placed, which depends on the build profile
+    pub build_directory: PathBuf,
+    pub profile: BuildProfile,
+    /// Whether to leave out the human-readable output, e.g. when a machine-readable summary is printed instead
+    pub quiet: bool,
+    /// Where to write a report of the test results, if anywhere
+    pub report: Option<ReportOption>,
+    /// Whether to stop starting new tests after the first failure
+    pub fail_fast: bool,
+    /// Whether to save the output of the program as the answer of each test
+    pub bless: bool,
+    /// The directory to save the output of the program for each test in, if it should be saved
+    pub output_directory: Option<PathBuf>,
+    /// Whether to remember which tests failed for `kat test --failed`
+    pub remember_failed_tests: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum OutputValidator {
+    /// Compare the output with the default output validator using the given flags
+    Default(ValidatorFlags),
+    /// Compare the output with a custom output validator (written by the user), in the same way as on Kattis
+    Custom(HelperProgram),
+}
+
+/// A program written by the user that is run alongside the solution, e.g. an output validator, an interactor,
+/// the generator and reference solution of `kat stress`, or the solution `kat bench` compares with
+#[derive(Debug, Clone)]
+pub struct HelperProgram {
+    pub file_path: PathBuf,
+    pub language: String,
+    pub flags: Vec<String>,
+}
+
+impl TestSettings {
+    /// Combines the options given on the command line with the problem config and the language config,
+    /// where the command line options take precedence
+    pub fn resolve(
+        app: &App,
+        language: &str,
+        problem_path: &Path,
+        options: &TestOptions,
+    ) -> Result<Self, Report> {
+        let problem_config = ProblemConfig::load(problem_path)?;
+        let language = app
+            .config
+            .kat_config
+            .languages
+            .get(language)
+            .wrap_err(format!(
+                "🙀 Could not find any language with the name: {}",
+                language
+            ))?;
+
+        let time_limit = match options.time_limit {
+            Some(time_limit) => time_limit,
+            None => {
+                let time_multiplier = language.time_multiplier.unwrap_or(1.0);
+                problem_config.time_limit.unwrap_or(DEFAULT_TIME_LIMIT) * time_multiplier
+            }
+        };
+
+        if !time_limit.is_finite() || time_limit <= 0.0 {
+            eyre::bail!("🙀 Invalid time limit: {}s", time_limit);
+        }
+
+        let memory_limit = options
+            .memory_limit
+            .or(problem_config.memory_limit)
+            .unwrap_or(DEFAULT_MEMORY_LIMIT);
+
+        let validator_flags = options
+            .validator_flags
+            .as_ref()
+            .or(problem_config.validator_flags.as_ref());
+
+        // custom output validators and interactors get the validator flags as extra arguments, like on Kattis
+        let judge_flags = match validator_flags {
+            Some(flags) => shlex::split(flags)
+                .ok_or_else(|| eyre::eyre!("🙀 Failed to split validator flags"))?,
+            None => Vec::new(),
+        };
+
+        let interactor = match options
+            .interactor
+            .as_ref()
+            .or(problem_config.interactor.as_ref())
+        {
+            Some(interactor) => {
+                let file_path = problem_path.join(interactor);
+                if !file_path.exists() {
+                    eyre::bail!("🙀 The interactor {} does not exist!", file_path.display());
+                }
+                Some(HelperProgram::new(app, file_path, judge_flags.clone())?)
+            }
+            None => None,
+        };
+
+        if options.bless && interactor.is_some() {
+            eyre::bail!("🙀 The answers of interactive problems are not the output of your program, so they can not be blessed!");
+        }
+
+        let validator = match find_output_validator(app, problem_path, &problem_config.validator)? {
+            Some(file_path) => {
+                OutputValidator::Custom(HelperProgram::new(app, file_path, judge_flags)?)
+            }
+            None => match validator_flags {
+                Some(flags) => OutputValidator::Default(ValidatorFlags::parse(flags)?),
+                None => OutputValidator::Default(ValidatorFlags::default()),
+            },
+        };
+
+        let build_directory =
+            find_build_directory(app, problem_path, &problem_config.build_directory)?;
+        // the builds of the debug profile are kept in a subdirectory, so that they do not overwrite the release builds
+        let build_directory = match options.profile {
+            BuildProfile::Release => build_directory,
+            BuildProfile::Debug => {
+                if language.compile_command.is_some() && language.compile_command_debug.is_none() {
+                    log::warn!("The language has no compile_command_debug, so the debug profile uses its compile_command");
+                }
+                build_directory.join(DEBUG_BUILD_DIRECTORY)
+            }
+        };
+
+        Ok(TestSettings {
+            limits: Limits {
+                time_limit: Duration::from_secs_f64(time_limit),
+                memory_limit: Some(memory_limit * 1024 * 1024),
+                // sanitizers reserve a lot of memory for their bookkeeping up front, which a limit on the address space would prevent
+                limit_address_space: language.limits_address_space()
+                    && options.profile == BuildProfile::Release,
+                stack_size: Some(language.stack_size.unwrap_or(memory_limit) * 1024 * 1024),
+                open_files: language.open_files,
+                output_limit: Some(
+                    language.output_limit.unwrap_or(DEFAULT_OUTPUT_LIMIT) * 1024 * 1024,
+                ),
+            },
+            validator,
+            interactor,
+            show_transcript: options.transcript,
+            jobs: match options.jobs {
+                Some(jobs) => jobs,
+                None => thread::available_parallelism().map_or(1, |jobs| jobs.get()),
+            },
+            pager: options.pager,
+            rebuild: options.rebuild,
+            quiet: false,
+            report: options.report.clone(),
+            fail_fast: options.fail_fast,
+            bless: options.bless,
+            output_directory: options
+                .save_output
+                .then(|| problem_path.join(OUTPUT_DIRECTORY)),
+            remember_failed_tests: true,
+            build_directory,
+            profile: options.profile,
+        })
+    }
+}
+
+impl HelperProgram {
+    pub fn new(app: &App, file_path: PathBuf, flags: Vec<String>) -> Result<Self, Report> {
+        let language = choose_language_by_extension(app, &file_path).wrap_err(format!(
+            "🙀 Failed to decide the language of the file {}",
+            file_path.display()
+        ))?;
+        Ok(HelperProgram {
+            file_path,
+            language,
+            flags,
+        })
+    }
+
+    pub fn file_name(&self) -> String {
+        self.file_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Builds the command that runs the program with the arguments `input judge_answer feedback_dir [flags]`
+    fn command(
+        &self,
+        app: &App,
+        build_directory: &Path,
+        test: &TestCase,
+        feedback_dir: &Path,
+    ) -> Result<Command, Report> {
+        let answer_file_path = test.answer.as_ref().wrap_err(format!(
+            "🙀 The test {} does not have an answer file",
+            test.name()
+        ))?;
+
+        let mut command = self.execute_command(app, build_directory)?;
+        command
+            .arg(&test.input)
+            .arg(answer_file_path)
+            .arg(feedback_dir)
+            .args(&self.flags);
+        Ok(command)
+    }
+
+    /// Builds the command that runs the program without any arguments
+    pub fn execute_command(&self, app: &App, build_directory: &Path) -> Result<Command, Report> {
+        let execute_command = &app.config.kat_config.languages[&self.language].execute_command;
+        build_command(execute_command, build_directory, &self.file_path)
+    }
+
+    /// Decides the judgement from the exit code of the program - 42 means accepted and 43 means wrong answer
+    fn judgement(
+        &self,
+        kind: &str,
+        output: &ProcessOutput,
+        feedback_dir: &Path,
+    ) -> Result<Judgement, Report> {
+        // the judge message is meant for the judges, but it is usually the most helpful message locally
+        let feedback = ["judgemessage.txt", "teammessage.txt"]
+            .iter()
+            .filter_map(|file| fs::read_to_string(feedback_dir.join(file)).ok())
+            .map(|message| message.trim().to_string())
+            .find(|message| !message.is_empty());
+
+        match output.status.code() {
+            Some(42) => {
+                if let Some(feedback) = feedback {
+                    log::info!("Feedback from the {kind}: {feedback}");
+                }
+                Ok(Judgement::Accepted)
+            }
+            Some(43) => {
+                Ok(Judgement::WrongAnswer(feedback.unwrap_or_else(|| {
+                    format!("the {kind} rejected the output")
+                })))
+            }
+            _ => {
+                eyre::bail!(
+                    "{}\n{}",
+                    format!(
+                        "{} The {kind} {} failed with {} - this is a problem with the {kind}, not your solution!",
+                        Verdict::JudgeError.emoji(),
+                        self.file_name(),
+                        output.status
+                    )
+                    .bright_magenta(),
+                    String::from_utf8_lossy(&output.stderr)
+                )
+            }
+        }
+    }
+}
+
+/// Compiles the problem, and the output validator and interactor if there are any
+pub fn compile_all(
+    app: &App,
+    problem_id: &str,
+    problem_path: &Path,
+    problem_file_path: &Path,
+    compile_command: &str,
+    settings: &TestSettings,
+) -> Result<(), Report> {
+    if !compile_command.is_empty() {
+        compile_problem(
+            compile_command,
+            problem_path,
+            problem_file_path,
+            &format!("problem: {problem_id}"),
+            settings,
+        )?;
     }
 
-    Ok(all_tests_passed)
+    if let OutputValidator::Custom(validator) = &settings.validator {
+        compile_helper_program(app, validator, problem_path, "output validator", settings)?;
+    }
+    if let Some(interactor) = &settings.interactor {
+        compile_helper_program(app, interactor, problem_path, "interactor", settings)?;
+    }
+    Ok(())
+}
+
+pub fn compile_helper_program(
+    app: &App,
+    program: &HelperProgram,
+    problem_path: &Path,
+    kind: &str,
+    settings: &TestSettings,
+) -> Result<(), Report> {
+    let language_config = &app.config.kat_config.languages[&program.language];
+    if let Some(compile_command) = language_config.compile_command_for(settings.profile) {
+        compile_problem(
+            compile_command,
+            problem_path,
+            &program.file_path,
+            &format!("{kind}: {}", program.file_name()),
+            settings,
+        )?;
+    }
+    Ok(())
 }
 
-fn compile_problem(
+/// Compiles a file, unless it was already compiled from the same source with the same compile command
+/// (and `--rebuild` is not set). The `name` describes what is compiled, e.g. "problem: hello".
+pub fn compile_problem(
     compile_command: &str,
     problem_path: &Path,
     problem_file_path: &Path,
+    name: &str,
+    settings: &TestSettings,
 ) -> Result<(), Report> {
-    let executable_path = problem_file_path.with_extension("");
+    let build_directory = &settings.build_directory;
+    let executable_path = executable_path(build_directory, problem_file_path);
+    // a build is only reused if the executable (or, if the compiler decides the file names, the build directory) is still there
+    let artifact = if compile_command.contains("{executable_path}") {
+        executable_path.clone()
+    } else {
+        build_directory.to_path_buf()
+    };
     let compile_command =
         &compile_command.replace("{executable_path}", executable_path.to_str().unwrap());
 
@@ -150,7 +957,32 @@ fn compile_problem(
         .split_once(' ')
         .ok_or_else(|| eyre::eyre!("🙀 Could not find arguments for compile command"))?;
 
-    let compile_args = prepare_arguments(compile_args, problem_file_path, problem_path)?;
+    let compile_args = prepare_arguments(compile_args, problem_file_path, build_directory)?;
+
+    let full_command: Vec<String> = std::iter::once(compile_cmd.to_string())
+        .chain(compile_args.iter().cloned())
+        .collect();
+    let stamp = BuildStamp::new(
+        problem_path,
+        problem_file_path,
+        &full_command,
+        Some(artifact),
+        settings.profile,
+    )?;
+    if !settings.rebuild && stamp.is_fresh() {
+        if !settings.quiet {
+            println!("♻️ Skipping compilation of {name}, as it has not changed");
+        }
+        return Ok(());
+    }
+    if !settings.quiet {
+        println!("🔨 Compiling {name} ...");
+    }
+    stamp.invalidate();
+    fs::create_dir_all(build_directory).wrap_err(format!(
+        "🙀 Failed to create the build directory {}",
+        build_directory.display()
+    ))?;
 
     let output = Command::new(compile_cmd)
         .args(compile_args)
@@ -165,26 +997,37 @@ fn compile_problem(
         })?;
 
     if !output.status.success() {
-        // print all output
-        println!("{}", String::from_utf8_lossy(&output.stdout));
-        println!("{}", String::from_utf8_lossy(&output.stderr));
-        eyre::bail!(
-            "🙀 Failed to compile problem: {}",
-            problem_file_path.display()
+        // print all output, to stderr if stdout is reserved for the machine-readable summary
+        let compiler_output = format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
         );
+        if settings.quiet {
+            eprintln!("{compiler_output}");
+        } else {
+            println!("{compiler_output}");
+        }
+        return Err(KatError::CompileError {
+            message: format!(
+                "🙀 Failed to compile problem: {}",
+                problem_file_path.display()
+            ),
+            output: compiler_output,
+        }
+        .into());
     }
 
-    Ok(())
+    stamp.save()
 }
 
-fn execute_problem(
-    app: &App,
+/// Builds the command that executes the given file, by expanding the placeholders in the execute command
+pub fn build_command(
     execute_command: &str,
-    problem_path: &Path,
-    problem_file_path: &Path,
-    test: (PathBuf, PathBuf),
-) -> Result<(), Report> {
-    let executable_path = problem_file_path.with_extension("");
+    build_directory: &Path,
+    file_path: &Path,
+) -> Result<Command, Report> {
+    let executable_path = executable_path(build_directory, file_path);
     let execute_command =
         &execute_command.replace("{executable_path}", executable_path.to_str().unwrap());
 
@@ -194,93 +1037,428 @@ fn execute_problem(
     };
 
     let execute_args = match execute_args {
-        Some(args) => prepare_arguments(args, problem_file_path, problem_path)?,
+        Some(args) => prepare_arguments(args, file_path, build_directory)?,
         None => Vec::new(),
     };
 
-    let (input_file_path, expected_output_file_path) = test;
-    let input_file_name = input_file_path
-        .file_name()
-        .expect("🙀 Failed to get file name from input file")
-        .to_str()
-        .wrap_err("🙀 Failed to convert file name to string")?
-        .to_string();
-    let input_file = File::open(input_file_path.clone())?;
+    let mut command = Command::new(execute_cmd);
+    command.args(execute_args);
+    Ok(command)
+}
 
-    let start_time = std::time::Instant::now();
-    let output = Command::new(execute_cmd)
-        .args(&execute_args)
-        .stdin(Stdio::from(input_file))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-        .map_err(|e| match e.kind() {
-            io::ErrorKind::NotFound => {
-                eyre::eyre!("🙀 Could not find execute command: {}", execute_cmd)
-            }
-            _ => eyre::eyre!("🙀 Failed to execute command with error: {}", e),
-        })?;
-    let elapsed_time = format!("{:.2}", start_time.elapsed().as_secs_f64());
+/// The path of the executable compiled from the given file, e.g. `hello.cpp.bin` in the build directory.
+/// The whole file name is used, so that solutions like `hello.c` and `hello.cpp` do not overwrite each other.
+fn executable_path(build_directory: &Path, file_path: &Path) -> PathBuf {
+    let file_name = file_path.file_name().unwrap_or_default().to_string_lossy();
+    build_directory.join(format!("{file_name}.bin"))
+}
 
-    if !output.status.success() {
-        // print all output
-        println!(
-            "{}",
-            format!("Output: {}", String::from_utf8_lossy(&output.stdout)).bold()
-        );
-        println!(
-            "{}",
-            format!("Error: {}", String::from_utf8_lossy(&output.stderr)).bold()
-        );
-        eyre::bail!(format!(
-            "🙀 Failed to execute problem: {}",
-            problem_file_path.display()
-        ));
+pub fn execute_error(command: &Command, e: io::Error) -> Report {
+    match e.kind() {
+        io::ErrorKind::NotFound => eyre::eyre!(
+            "🙀 Could not find execute command: {}",
+            command.get_program().to_string_lossy()
+        ),
+        _ => eyre::eyre!("🙀 Failed to execute command with error: {}", e),
+    }
+}
+
+pub fn execute_problem(
+    app: &App,
+    execute_command: &str,
+    problem_file_path: &Path,
+    test: &TestCase,
+    settings: &TestSettings,
+) -> Result<TestResult, Report> {
+    let limits = &settings.limits;
+    let mut command = build_command(
+        execute_command,
+        &settings.build_directory,
+        problem_file_path,
+    )?;
+
+    let input_file_name = test.name();
+    let expected_output_file_path = match &test.answer {
+        Some(answer) => answer.clone(),
+        // the answer is created from the output when blessing
+        None if settings.bless => test.input.with_extension("ans"),
+        None => eyre::bail!("🙀 The test {input_file_name} does not have an answer file"),
+    };
+    let input_file = File::open(&test.input)?;
+
+    let output = run_limited(&mut command, Stdio::from(input_file), limits)
+        .map_err(|e| execute_error(&command, e))?;
+    let usage = format_usage(&output);
+
+    // the output is not saved next to the test, as a .out file there is taken to be the answer of the test
+    if let Some(output_directory) = &settings.output_directory {
+        let output_file_path = output_directory.join(Path::new(&test.name()).with_extension("out"));
+        if let Some(parent) = output_file_path.parent() {
+            fs::create_dir_all(parent).wrap_err("🙀 Failed to create the output directory")?;
+        }
+        fs::write(&output_file_path, &output.stdout).wrap_err(format!(
+            "🙀 Failed to save the output to {}",
+            output_file_path.display()
+        ))?;
+    }
+
+    if let Some((verdict, report)) = check_run(&output, limits, &input_file_name) {
+        return Ok(TestResult::failed(verdict, report, Some(output)));
+    }
+
+    if settings.bless {
+        let expected_output = fs::read(&expected_output_file_path).unwrap_or_default();
+        if !expected_output_file_path.exists() || expected_output != output.stdout {
+            fs::write(&expected_output_file_path, &output.stdout).wrap_err(format!(
+                "🙀 Failed to save the output to {}",
+                expected_output_file_path.display()
+            ))?;
+            return Ok(TestResult::blessed(&input_file_name, output));
+        }
     }
 
     // Compare the output of the program to the expected output
-    let expected_output = fs::read_to_string(expected_output_file_path)?;
+    let expected_output = fs::read_to_string(&expected_output_file_path)?;
     let actual_output = String::from_utf8_lossy(&output.stdout).into_owned();
-    if actual_output != expected_output {
-        match app.args.verbose.log_level() {
-            Some(log::Level::Error) | None => {
-                eyre::bail!(
-                    "{}{}",
-                    format!("❌ Test {input_file_name} failed!\n").bright_red(),
-                    format!("Output:\n{}", actual_output)
-                )
-            }
-            Some(_) => {
-                let stderr_output = String::from_utf8_lossy(&output.stderr);
-                let error_output = if !String::from_utf8_lossy(&output.stderr).is_empty() {
-                    format!("\nError output:\n{}\n", stderr_output)
-                } else {
-                    String::new()
-                };
-                eyre::bail!(
-                    "{}{}",
-                    format!("❌ Test {input_file_name} failed!\n").bright_red(),
-                    format!(
-                        "Expected output:\n{}\nActual output:\n{}{}",
-                        expected_output, actual_output, error_output,
-                    )
-                    .bold()
-                )
-            }
+    let judgement = match &settings.validator {
+        OutputValidator::Default(flags) => validate_output(&expected_output, &actual_output, flags),
+        OutputValidator::Custom(validator) => run_output_validator(
+            app,
+            validator,
+            &settings.build_directory,
+            test,
+            &output.stdout,
+        )?,
+    };
+    if let Judgement::WrongAnswer(reason) = judgement {
+        // lines are only considered different if the default validator would reject them
+        let diff_flags = match &settings.validator {
+            OutputValidator::Default(flags) => flags.clone(),
+            OutputValidator::Custom(_) => ValidatorFlags::default(),
+        };
+        let mut report = format!(
+            "{}{}\n{}",
+            format!(
+                "{}\n",
+                failure_summary(Verdict::WrongAnswer, &input_file_name, None, &usage)
+            )
+            .bright_red(),
+            reason.bold(),
+            format_diff(&expected_output, &actual_output, &diff_flags)
+        );
+        let stderr_output = String::from_utf8_lossy(&output.stderr);
+        // sanitizers that are set up to recover from errors do not make the program crash, but they should not go unnoticed
+        report.push_str(&format_sanitizer_report(&stderr_output));
+        if app.args.verbose.log_level() > Some(log::Level::Error) && !stderr_output.is_empty() {
+            report.push_str(&format!("{}\n{}", "Error output:".bold(), stderr_output));
         }
+        Ok(TestResult::failed(
+            Verdict::WrongAnswer,
+            report,
+            Some(output),
+        ))
     } else {
-        println!(
-            "{}",
-            format!("✅ Test {} passed in {}s!", input_file_name, elapsed_time).bright_green()
-        );
-        Ok(())
+        Ok(TestResult::accepted(&input_file_name, output))
+    }
+}
+
+/// Checks that the program did not exceed the time, memory or output limits, and that it exited successfully.
+/// Returns the verdict and report of the test if it failed.
+fn check_run(
+    output: &ProcessOutput,
+    limits: &Limits,
+    test_name: &str,
+) -> Option<(Verdict, String)> {
+    let usage = format_usage(output);
+
+    if output.exceeded_time_limit(limits) {
+        return Some((
+            Verdict::TimeLimitExceeded,
+            failure_summary(
+                Verdict::TimeLimitExceeded,
+                test_name,
+                Some(&format!(
+                    "it ran for more than {:.2}s",
+                    limits.time_limit.as_secs_f64()
+                )),
+                &usage,
+            )
+            .bright_red()
+            .to_string(),
+        ));
+    }
+
+    if output.exceeded_memory_limit(limits) {
+        return Some((
+            Verdict::MemoryLimitExceeded,
+            failure_summary(
+                Verdict::MemoryLimitExceeded,
+                test_name,
+                Some(&format!(
+                    "it used more than {}MB",
+                    limits.memory_limit.unwrap_or_default() / (1024 * 1024)
+                )),
+                &usage,
+            )
+            .bright_red()
+            .to_string(),
+        ));
+    }
+
+    if output.exceeded_output_limit {
+        return Some((
+            Verdict::OutputLimitExceeded,
+            failure_summary(
+                Verdict::OutputLimitExceeded,
+                test_name,
+                Some(&format!(
+                    "it wrote more than {}MB",
+                    limits.output_limit.unwrap_or_default() / (1024 * 1024)
+                )),
+                &usage,
+            )
+            .bright_red()
+            .to_string(),
+        ));
+    }
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let crash = describe_crash(&output.status, &stderr);
+        return Some((
+            Verdict::RunTimeError,
+            format!(
+                "{}\n{}\n{}{}\n{}",
+                failure_summary(
+                    Verdict::RunTimeError,
+                    test_name,
+                    Some(&crash.reason),
+                    &usage
+                )
+                .bright_red(),
+                format!("💡 {}", crash.hint).bright_yellow(),
+                format_sanitizer_report(&stderr),
+                format!("Output: {}", String::from_utf8_lossy(&output.stdout)).bold(),
+                format!("Error: {stderr}").bold(),
+            ),
+        ));
+    }
+
+    None
+}
+
+/// The first line of the report of a failed test, with the emoji and name of its verdict and what went wrong,
+/// e.g. "⌛️ Test 3.in failed with Time Limit Exceeded - it ran for more than 1.00s! (CPU: ...)"
+fn failure_summary(
+    verdict: Verdict,
+    test_name: &str,
+    details: Option<&str>,
+    usage: &str,
+) -> String {
+    let details = details.map_or(String::new(), |details| format!(" - {details}"));
+    format!(
+        "{} Test {test_name} failed with {}{details}! {usage}",
+        verdict.emoji(),
+        verdict.name()
+    )
+}
+
+/// The errors reported by sanitizers in the error output, highlighted, or nothing if there are none
+fn format_sanitizer_report(stderr: &str) -> String {
+    match sanitizer_report(stderr) {
+        Some(report) => format!(
+            "{}\n{}\n",
+            "🧼 Sanitizer report:".bold(),
+            report.bright_red()
+        ),
+        None => String::new(),
+    }
+}
+
+/// Runs a custom output validator like Kattis does, i.e. with the output of the program on stdin
+fn run_output_validator(
+    app: &App,
+    validator: &HelperProgram,
+    build_directory: &Path,
+    test: &TestCase,
+    output: &[u8],
+) -> Result<Judgement, Report> {
+    let feedback_dir = tempfile::tempdir().wrap_err("🙀 Failed to create feedback directory")?;
+    let mut command = validator.command(app, build_directory, test, feedback_dir.path())?;
+
+    let mut output_file = tempfile::tempfile().wrap_err("🙀 Failed to create temporary file")?;
+    output_file
+        .write_all(output)
+        .and_then(|_| output_file.seek(SeekFrom::Start(0)))
+        .wrap_err("🙀 Failed to write program output to temporary file")?;
+
+    let validator_output = run_limited(&mut command, Stdio::from(output_file), &helper_limits())
+        .map_err(|e| execute_error(&command, e))?;
+
+    validator.judgement("output validator", &validator_output, feedback_dir.path())
+}
+
+pub fn helper_limits() -> Limits {
+    Limits {
+        time_limit: HELPER_TIME_LIMIT,
+        memory_limit: None,
+        limit_address_space: false,
+        stack_size: None,
+        open_files: None,
+        output_limit: None,
     }
 }
 
-fn prepare_arguments(
+/// Runs the program against the interactor, with the stdout of each connected to the stdin of the other
+fn execute_interactive(
+    app: &App,
+    execute_command: &str,
+    problem_file_path: &Path,
+    test: &TestCase,
+    interactor: &HelperProgram,
+    settings: &TestSettings,
+) -> Result<TestResult, Report> {
+    let limits = &settings.limits;
+    let test_name = test.name();
+    let mut command = build_command(
+        execute_command,
+        &settings.build_directory,
+        problem_file_path,
+    )?;
+    let feedback_dir = tempfile::tempdir().wrap_err("🙀 Failed to create feedback directory")?;
+    let mut interactor_command =
+        interactor.command(app, &settings.build_directory, test, feedback_dir.path())?;
+
+    let (output, interactor_output, transcript) = run_interactive(
+        &mut command,
+        &mut interactor_command,
+        limits,
+        &helper_limits(),
+    )
+    .map_err(|e| execute_error(&command, e))?;
+
+    let transcript = if settings.show_transcript {
+        format_transcript_report(&test_name, &format_transcript(&transcript))
+    } else {
+        String::new()
+    };
+
+    // when the program is killed for running too long, the interactor will most likely reject the output,
+    // so the time limit is checked first. Otherwise the verdict of the interactor takes precedence.
+    let judgement = if output.exceeded_time_limit(limits) {
+        None
+    } else {
+        Some(interactor.judgement("interactor", &interactor_output, feedback_dir.path())?)
+    };
+    Ok(interactive_result(
+        &test_name, transcript, judgement, output, limits,
+    ))
+}
+
+/// Replays a sample interaction (a `.interaction` file) against the program
+fn replay_interaction(
+    execute_command: &str,
+    problem_file_path: &Path,
+    test: &TestCase,
+    settings: &TestSettings,
+) -> Result<TestResult, Report> {
+    let limits = &settings.limits;
+    let test_name = test.name();
+    let interaction = fs::read_to_string(&test.input)?;
+    let flags = match &settings.validator {
+        OutputValidator::Default(flags) => flags.clone(),
+        OutputValidator::Custom(_) => ValidatorFlags::default(),
+    };
+
+    let mut command = build_command(
+        execute_command,
+        &settings.build_directory,
+        problem_file_path,
+    )?;
+    let mut child = spawn_limited(&mut command, Stdio::piped(), limits)
+        .map_err(|e| execute_error(&command, e))?;
+    let stdin = child.stdin.take().expect("stdin is piped");
+    let stdout = child.take_stdout().expect("stdout is piped");
+    let interactor = thread::spawn(move || play_interaction(&interaction, stdin, stdout, &flags));
+
+    let output = child.wait().map_err(|e| execute_error(&command, e))?;
+    let (judgement, transcript) = interactor
+        .join()
+        .map_err(|_| eyre::eyre!("🙀 Failed to replay the interaction {test_name}"))?;
+
+    let transcript = if settings.show_transcript {
+        format_transcript_report(&test_name, &format_transcript(&transcript))
+    } else {
+        String::new()
+    };
+
+    Ok(interactive_result(
+        &test_name,
+        transcript,
+        Some(judgement),
+        output,
+        limits,
+    ))
+}
+
+fn format_transcript_report(test_name: &str, transcript: &str) -> String {
+    format!(
+        "{}\n{}\n",
+        format!("📜 Transcript of test {test_name}:").bold(),
+        transcript
+    )
+}
+
+/// Combines the judgement of the interactor with the checks of the program run into the result of the test.
+/// The judgement is `None` if the interactor was not consulted, because the program ran for too long.
+/// A program that wrote too much output is cut off, so the interactor most likely rejects it, and the
+/// Output Limit Exceeded takes precedence over that.
+fn interactive_result(
+    test_name: &str,
+    transcript: String,
+    judgement: Option<Judgement>,
+    output: ProcessOutput,
+    limits: &Limits,
+) -> TestResult {
+    let failure = match judgement {
+        Some(Judgement::WrongAnswer(reason)) if !output.exceeded_output_limit => Some((
+            Verdict::WrongAnswer,
+            format!(
+                "{}\n{}",
+                failure_summary(
+                    Verdict::WrongAnswer,
+                    test_name,
+                    None,
+                    &format_usage(&output)
+                )
+                .bright_red(),
+                reason.bold(),
+            ),
+        )),
+        _ => check_run(&output, limits, test_name),
+    };
+
+    let mut result = match failure {
+        Some((verdict, report)) => TestResult::failed(verdict, report, Some(output)),
+        None => TestResult::accepted(test_name, output),
+    };
+    result.report = transcript + &result.report;
+    result
+}
+
+pub fn format_usage(output: &ProcessOutput) -> String {
+    format!(
+        "(CPU: {:.2}s user, {:.2}s sys - Memory: {:.1}MB)",
+        output.user_time.as_secs_f64(),
+        output.system_time.as_secs_f64(),
+        output.peak_memory as f64 / (1024.0 * 1024.0)
+    )
+}
+
+pub fn prepare_arguments(
     compile_args: &str,
     problem_file_path: &Path,
-    problem_path: &Path,
+    build_directory: &Path,
 ) -> Result<Vec<String>, Report> {
     let mut compile_args =
         shlex::split(compile_args).ok_or_else(|| eyre::eyre!("🙀 Failed to split arguments"))?;
@@ -291,10 +1469,11 @@ fn prepare_arguments(
 
     for arg in compile_args.iter_mut() {
         if arg.contains("{output_directory}") {
-            *arg = arg.replace("{output_directory}", problem_path.to_str().unwrap());
+            *arg = arg.replace("{output_directory}", build_directory.to_str().unwrap());
         }
         if arg.contains("{executable_path}") {
-            *arg = arg.replace("{executable_path}", executable.to_str().unwrap());
+            let executable_path = executable_path(build_directory, problem_file_path);
+            *arg = arg.replace("{executable_path}", executable_path.to_str().unwrap());
         }
         if arg.contains("{source_file}") {
             *arg = arg.replace("{source_file}", problem_file_path.to_str().unwrap());
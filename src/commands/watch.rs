@@ -1,11 +1,11 @@
 use crate::{
     cli::Watch,
-    commands::test::test_problem,
-    utils::{find_problem_dir, find_test_files, get_problem_file},
+    commands::test::{test_problem, TestSettings},
+    utils::{find_problem_dir, find_test_files, get_problem_file, TestCase},
     App,
 };
 
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 use color_eyre::{
     self,
@@ -26,7 +26,8 @@ pub async fn watch(app: &App, args: &Watch) -> Result<(), Report> {
     let (problem_path, problem_id) = find_problem_dir(app, &args.path)?;
     let (problem_file, problem_file_path, language) =
         get_problem_file(app, &args.file, &args.language, &problem_path, &problem_id)?;
-    let tests = find_test_files(app, &args.test_cases, &problem_path)?;
+    let tests = find_test_files(app, &args.test_cases, &problem_path, args.options.bless)?;
+    let settings = TestSettings::resolve(app, &language, &problem_path, &args.options)?;
 
     println!(
         "{}",
@@ -45,6 +46,7 @@ pub async fn watch(app: &App, args: &Watch) -> Result<(), Report> {
         &problem_file_path,
         tests,
         &language,
+        &settings,
     )
     .await?;
 
@@ -56,8 +58,9 @@ async fn watch_problem(
     problem_id: &str,
     problem_path: &Path,
     problem_file_path: &Path,
-    tests: Vec<(PathBuf, PathBuf)>,
+    tests: Vec<TestCase>,
     language: &str,
+    settings: &TestSettings,
 ) -> Result<(), Report> {
     let problem_file = problem_file_path
         .file_name()
@@ -66,14 +69,16 @@ async fn watch_problem(
         .wrap_err("🙀 Failed to convert file name to string")?;
 
     // Test the problem once before starting to watch
-    if test_problem(
+    let run = test_problem(
         app,
         problem_id,
         problem_path,
         problem_file_path,
         tests.clone(),
         language,
-    )? {
+        settings,
+    )?;
+    if run.passed {
         print_pass_message(problem_id, problem_file);
     }
     println!("{}", "=".repeat(25).bright_cyan()); // Separator line
@@ -104,14 +109,16 @@ async fn watch_problem(
                             "{}",
                             "👀 File changed, testing again ...".bold().bright_blue()
                         );
-                        if test_problem(
+                        let run = test_problem(
                             app,
                             problem_id,
                             problem_path,
                             problem_file_path,
                             tests.clone(),
                             language,
-                        )? {
+                            settings,
+                        )?;
+                        if run.passed {
                             print_pass_message(problem_id, problem_file);
                         }
                         println!("{}", "=".repeat(25).bright_cyan()); // Separator line
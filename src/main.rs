@@ -2,7 +2,7 @@ pub mod cli;
 mod commands;
 mod utils;
 use cli::parse_cli;
-use utils::AppConfig;
+use utils::{errors::KatError, AppConfig};
 
 use color_eyre::{Report, Result};
 
@@ -50,9 +50,14 @@ async fn attempt_run(args: cli::Cli) -> crate::Result<()> {
         let app = App { args, config };
 
         match &app.args.subcommand {
+            Bench(args) => commands::bench(&app, args).await,
+            Clean(args) => commands::clean(&app, args).await,
             Config(args) => commands::config(&app, args).await,
+            Debug(args) => commands::debug(&app, args).await,
             Get(args) => commands::get(&app, args).await,
             Open(args) => commands::open(&app, args).await,
+            Run(args) => commands::run(&app, args).await,
+            Stress(args) => commands::stress(&app, args).await,
             Submit(args) => commands::submit(&app, args).await,
             Test(args) => commands::test(&app, args).await,
             Watch(args) => commands::watch(&app, args).await,
@@ -64,7 +69,11 @@ async fn attempt_run(args: cli::Cli) -> crate::Result<()> {
 
 fn exit_on_err(res: crate::Result<()>, verbose: clap_verbosity_flag::Verbosity) {
     if let Err(e) = res {
+        let kat_error = e.downcast_ref::<KatError>();
+        // the reports of the failed tests have already been printed, so there is nothing to add
+        let already_reported = matches!(kat_error, Some(KatError::TestsFailed));
         match verbose.log_level() {
+            _ if already_reported => {}
             Some(log::Level::Error) => {
                 // If Error (default), we want to print a short error report
                 eprintln!("{}", format!("Error: {e}").bright_red());
@@ -78,6 +87,6 @@ fn exit_on_err(res: crate::Result<()>, verbose: clap_verbosity_flag::Verbosity)
             }
         }
 
-        std::process::exit(1);
+        std::process::exit(kat_error.map_or(1, KatError::exit_code));
     }
 }
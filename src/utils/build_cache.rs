@@ -0,0 +1,88 @@
+use crate::cli::BuildProfile;
+
+use color_eyre::{eyre::Context, Report};
+use sha2::{Digest, Sha256};
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// The directory (within the problem directory) where kat keeps its build stamps
+const CACHE_DIRECTORY: &str = ".kat/cache";
+
+/// Remembers what a file was last compiled from, so that unchanged files are not compiled again.
+/// The stamp is a hash of the source file and the fully expanded compile command.
+#[derive(Debug)]
+pub struct BuildStamp {
+    path: PathBuf,
+    hash: String,
+    artifact: Option<PathBuf>,
+}
+
+impl BuildStamp {
+    /// Creates the stamp for compiling `source_file` with `compile_command`. If the compile command produces
+    /// a known `artifact`, the build is only reused when the artifact still exists.
+    /// Each build profile has its own stamp, as the builds of the profiles are kept apart.
+    pub fn new(
+        problem_path: &Path,
+        source_file: &Path,
+        compile_command: &[String],
+        artifact: Option<PathBuf>,
+        profile: BuildProfile,
+    ) -> Result<Self, Report> {
+        let source = fs::read(source_file).wrap_err(format!(
+            "🙀 Failed to read the source file {}",
+            source_file.display()
+        ))?;
+
+        let mut hasher = Sha256::new();
+        hasher.update(&source);
+        for argument in compile_command {
+            hasher.update([0]);
+            hasher.update(argument.as_bytes());
+        }
+        let hash = hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        let file_name = source_file
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let stamp_name = match profile {
+            BuildProfile::Release => format!("{file_name}.sha256"),
+            BuildProfile::Debug => format!("{file_name}.debug.sha256"),
+        };
+        Ok(BuildStamp {
+            path: problem_path.join(CACHE_DIRECTORY).join(stamp_name),
+            hash,
+            artifact,
+        })
+    }
+
+    /// Whether the last successful build was made from the same source and compile command
+    pub fn is_fresh(&self) -> bool {
+        let artifact_exists = self
+            .artifact
+            .as_ref()
+            .is_none_or(|artifact| artifact.exists());
+        artifact_exists
+            && fs::read_to_string(&self.path).is_ok_and(|stamp| stamp.trim() == self.hash)
+    }
+
+    /// Forgets the last build, e.g. before compiling, so that a failed build is never reused
+    pub fn invalidate(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+
+    /// Records a successful build
+    pub fn save(&self) -> Result<(), Report> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).wrap_err("🙀 Failed to create the build cache directory")?;
+        }
+        fs::write(&self.path, &self.hash).wrap_err("🙀 Failed to save the build stamp")
+    }
+}
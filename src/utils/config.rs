@@ -1,8 +1,10 @@
+use crate::cli::BuildProfile;
+
 use std::{
     collections::HashMap,
     fs::{self, OpenOptions},
     io::Write,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 
 use color_eyre::{
@@ -156,14 +158,49 @@ impl Kattisrc {
 #[derive(Debug, Deserialize)]
 pub struct Language {
     pub compile_command: Option<String>,
+    /// The command to compile a program with for debugging, e.g. with debug symbols and sanitizers
+    pub compile_command_debug: Option<String>,
     pub execute_command: String,
+    /// The command that `kat debug` runs a program with, put in front of the execute command, e.g. "gdb --args"
+    pub debug_wrapper: Option<String>,
     pub extensions: Vec<String>,
     pub template: Option<String>,
+    pub time_multiplier: Option<f64>,
+    /// Whether the memory limit is also enforced as a limit on the address space, which languages have to opt in to
+    pub limit_address_space: Option<bool>,
+    /// The stack size in megabytes, which is the memory limit of the problem by default, like on Kattis
+    pub stack_size: Option<u64>,
+    /// The maximum number of files a program can have open at the same time
+    pub open_files: Option<u64>,
+    /// The maximum size of the output of a program in megabytes
+    pub output_limit: Option<u64>,
+}
+
+impl Language {
+    /// Whether the memory limit should be enforced as a limit on the address space of the program. This is off unless
+    /// the language turns it on, as runtimes like the JVM reserve a lot more memory up front than they ever use.
+    /// Otherwise only the peak memory usage of the program is checked against the memory limit.
+    pub fn limits_address_space(&self) -> bool {
+        self.limit_address_space.unwrap_or(false)
+    }
+
+    /// The command to compile a program with in the given build profile, where the debug profile falls back to
+    /// the normal compile command if the language does not have a debug one
+    pub fn compile_command_for(&self, profile: BuildProfile) -> Option<&str> {
+        match profile {
+            BuildProfile::Release => self.compile_command.as_deref(),
+            BuildProfile::Debug => self
+                .compile_command_debug
+                .as_deref()
+                .or(self.compile_command.as_deref()),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Default {
     pub language: String,
+    pub build_directory: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -200,6 +237,54 @@ impl KatConfig {
     }
 }
 
+// The name of the (optional) per-problem config file, which is stored in the problem directory
+const PROBLEM_CONFIG_FILE: &str = "kat.toml";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ProblemConfig {
+    /// The CPU time limit of the problem in seconds, as given on Kattis
+    pub time_limit: Option<f64>,
+    /// The memory limit of the problem in megabytes, as given on Kattis
+    pub memory_limit: Option<u64>,
+    /// The flags for the output validator, in the same format as in a Kattis problem package
+    pub validator_flags: Option<String>,
+    /// The path of a custom output validator, relative to the problem directory
+    pub validator: Option<String>,
+    /// The path of the interactor for interactive problems, relative to the problem directory
+    pub interactor: Option<String>,
+    /// The directory where the compiled files are placed, overriding the one in the config file
+    pub build_directory: Option<String>,
+}
+
+impl ProblemConfig {
+    pub fn load(problem_path: &Path) -> Result<Self, Report> {
+        let config_file = problem_path.join(PROBLEM_CONFIG_FILE);
+        if !config_file.exists() {
+            return Ok(ProblemConfig::default());
+        }
+
+        let config = config::Config::builder()
+            .add_source(File::from(config_file.clone()).format(FileFormat::Toml))
+            .build()
+            .wrap_err("🙀 Failed to build the problem config, make sure kat.toml is in the correct format!")?;
+
+        let problem_config: ProblemConfig = config.try_deserialize().wrap_err_with(|| {
+            format!(
+                "🙀 Failed to parse the problem config at {}!",
+                config_file.display()
+            )
+        })?;
+        Ok(problem_config)
+    }
+
+    pub fn save(&self, problem_path: &Path) -> Result<(), Report> {
+        let toml = to_string_pretty(&self)?;
+        fs::write(problem_path.join(PROBLEM_CONFIG_FILE), toml)
+            .wrap_err("🙀 Failed to write the problem config")?;
+        Ok(())
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
     pub kattisrc: Kattisrc,
@@ -219,3 +304,32 @@ impl AppConfig {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn language(config: &str) -> Language {
+        toml::from_str(config).unwrap()
+    }
+
+    #[test]
+    fn address_space_is_only_limited_when_the_language_opts_in() {
+        let java = language(
+            r#"
+            execute_command = "java -cp {output_directory} {source_file_no_ext}"
+            extensions = ["java"]
+            "#,
+        );
+        assert!(!java.limits_address_space());
+
+        let c = language(
+            r#"
+            execute_command = "{executable_path}"
+            extensions = ["c"]
+            limit_address_space = true
+            "#,
+        );
+        assert!(c.limits_address_space());
+    }
+}
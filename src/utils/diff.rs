@@ -0,0 +1,464 @@
+use crate::utils::validator::{validate_output, Judgement, ValidatorFlags};
+
+use colored::Colorize;
+
+/// The number of unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+/// The maximum number of lines of the diff that are shown
+const MAX_DIFF_LINES: usize = 100;
+/// The maximum number of characters shown of a single line
+const MAX_LINE_LENGTH: usize = 160;
+/// The largest number of line pairs that are compared to align the outputs, larger outputs are compared line by line
+const MAX_ALIGNMENT_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Formats a colored, unified diff between the expected output and the output of a program.
+/// Lines are compared like the default output validator would compare them, unchanged lines far from a change
+/// are left out, and the first differing token is highlighted. Very long diffs are truncated with a summary.
+pub fn format_diff(expected: &str, actual: &str, flags: &ValidatorFlags) -> String {
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    let (edits, aligned) = diff_lines(&expected_lines, &actual_lines, flags);
+
+    let removed_lines = edits
+        .iter()
+        .filter(|edit| matches!(edit, Edit::Delete(_)))
+        .count();
+    let added_lines = edits
+        .iter()
+        .filter(|edit| matches!(edit, Edit::Insert(_)))
+        .count();
+    let mut diff = format!("{}\n{}\n", "--- expected".red(), "+++ actual".green());
+    if removed_lines + added_lines == 0 {
+        diff.push_str(
+            &"(no lines differ - the difference is in the whitespace between or after the lines)\n"
+                .dimmed()
+                .to_string(),
+        );
+        return diff;
+    }
+
+    let highlight = first_difference(&edits, &expected_lines, &actual_lines, flags);
+
+    let mut shown_lines = 0;
+    let mut previous_end = 0;
+    let mut truncated = false;
+    'hunks: for (start, end) in hunks(&edits) {
+        if start > previous_end {
+            diff.push_str(
+                &format!("⋮ {} unchanged lines\n", start - previous_end)
+                    .dimmed()
+                    .to_string(),
+            );
+        }
+        diff.push_str(&hunk_header(&edits[start..end]).cyan().to_string());
+        diff.push('\n');
+
+        for (index, edit) in edits.iter().enumerate().take(end).skip(start) {
+            if shown_lines == MAX_DIFF_LINES {
+                diff.push_str(
+                    &format!(
+                        "… the diff was truncated after {MAX_DIFF_LINES} lines - in total {removed_lines} of the {} expected lines were removed and {added_lines} of the {} actual lines were added\n",
+                        expected_lines.len(),
+                        actual_lines.len()
+                    )
+                    .dimmed()
+                    .to_string(),
+                );
+                truncated = true;
+                break 'hunks;
+            }
+            shown_lines += 1;
+
+            let line = match *edit {
+                Edit::Equal(_, j) => format!(" {}", clip(actual_lines[j], None))
+                    .dimmed()
+                    .to_string(),
+                Edit::Delete(i) => {
+                    let token = highlight
+                        .expected
+                        .filter(|_| highlight.expected_edit == Some(index));
+                    highlight_line('-', expected_lines[i], token)
+                }
+                Edit::Insert(j) => {
+                    let token = highlight
+                        .actual
+                        .filter(|_| highlight.actual_edit == Some(index));
+                    highlight_line('+', actual_lines[j], token)
+                }
+            };
+            diff.push_str(&line);
+            diff.push('\n');
+        }
+        previous_end = end;
+    }
+    if !truncated && previous_end < edits.len() {
+        diff.push_str(
+            &format!("⋮ {} unchanged lines\n", edits.len() - previous_end)
+                .dimmed()
+                .to_string(),
+        );
+    }
+    if !aligned {
+        diff.push_str(
+            &"(the outputs were too large to align, so the lines were compared one by one)\n"
+                .dimmed()
+                .to_string(),
+        );
+    }
+
+    diff
+}
+
+/// Finds the edits that turn the expected lines into the actual lines, using the longest common subsequence
+/// of the lines that differ. Returns whether the lines could be aligned, or were just compared by position.
+fn diff_lines(expected: &[&str], actual: &[&str], flags: &ValidatorFlags) -> (Vec<Edit>, bool) {
+    // comparing normalized lines is a lot faster than running the validator on every pair of lines
+    let expected_keys: Vec<String> = expected.iter().map(|line| line_key(line, flags)).collect();
+    let actual_keys: Vec<String> = actual.iter().map(|line| line_key(line, flags)).collect();
+    let lines_match = |i: usize, j: usize| {
+        expected_keys[i] == actual_keys[j]
+            || (flags.has_float_tolerance() && lines_match(expected[i], actual[j], flags))
+    };
+
+    let mut prefix = 0;
+    while prefix < expected.len() && prefix < actual.len() && lines_match(prefix, prefix) {
+        prefix += 1;
+    }
+    let mut suffix = 0;
+    while suffix < expected.len() - prefix
+        && suffix < actual.len() - prefix
+        && lines_match(expected.len() - suffix - 1, actual.len() - suffix - 1)
+    {
+        suffix += 1;
+    }
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Equal(i, i)).collect();
+    let (n, m) = (
+        expected.len() - prefix - suffix,
+        actual.len() - prefix - suffix,
+    );
+    let aligned = n.saturating_mul(m) <= MAX_ALIGNMENT_CELLS;
+
+    if aligned {
+        // lcs[i][j] is the length of the longest common subsequence of the remaining lines from i and j
+        let mut lcs = vec![0u32; (n + 1) * (m + 1)];
+        let at = |i: usize, j: usize| i * (m + 1) + j;
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[at(i, j)] = if lines_match(prefix + i, prefix + j) {
+                    lcs[at(i + 1, j + 1)] + 1
+                } else {
+                    lcs[at(i + 1, j)].max(lcs[at(i, j + 1)])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && lines_match(prefix + i, prefix + j) {
+                edits.push(Edit::Equal(prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if j == m || (i < n && lcs[at(i + 1, j)] >= lcs[at(i, j + 1)]) {
+                edits.push(Edit::Delete(prefix + i));
+                i += 1;
+            } else {
+                edits.push(Edit::Insert(prefix + j));
+                j += 1;
+            }
+        }
+    } else {
+        for k in 0..n.max(m) {
+            if k < n && k < m && lines_match(prefix + k, prefix + k) {
+                edits.push(Edit::Equal(prefix + k, prefix + k));
+                continue;
+            }
+            if k < n {
+                edits.push(Edit::Delete(prefix + k));
+            }
+            if k < m {
+                edits.push(Edit::Insert(prefix + k));
+            }
+        }
+    }
+
+    edits.extend(
+        (0..suffix).map(|k| Edit::Equal(expected.len() - suffix + k, actual.len() - suffix + k)),
+    );
+    (edits, aligned)
+}
+
+/// Normalizes a line, such that two lines are accepted by the validator if their keys are equal
+/// (or, when the validator has a float tolerance, possibly if they are different)
+fn line_key(line: &str, flags: &ValidatorFlags) -> String {
+    let line = if flags.space_change_sensitive {
+        line.to_string()
+    } else {
+        line.split_whitespace().collect::<Vec<_>>().join(" ")
+    };
+    if flags.case_sensitive {
+        line
+    } else {
+        line.to_lowercase()
+    }
+}
+
+fn lines_match(expected: &str, actual: &str, flags: &ValidatorFlags) -> bool {
+    if expected == actual {
+        return true;
+    }
+    validate_output(expected, actual, flags) == Judgement::Accepted
+}
+
+/// Groups the changes into ranges of edits, each with some unchanged lines around it
+fn hunks(edits: &[Edit]) -> Vec<(usize, usize)> {
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (index, edit) in edits.iter().enumerate() {
+        if matches!(edit, Edit::Equal(..)) {
+            continue;
+        }
+        let start = index.saturating_sub(CONTEXT_LINES);
+        let end = (index + CONTEXT_LINES + 1).min(edits.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    hunks
+}
+
+fn hunk_header(edits: &[Edit]) -> String {
+    let expected_lines: Vec<usize> = edits
+        .iter()
+        .filter_map(|edit| match *edit {
+            Edit::Equal(i, _) | Edit::Delete(i) => Some(i),
+            Edit::Insert(_) => None,
+        })
+        .collect();
+    let actual_lines: Vec<usize> = edits
+        .iter()
+        .filter_map(|edit| match *edit {
+            Edit::Equal(_, j) | Edit::Insert(j) => Some(j),
+            Edit::Delete(_) => None,
+        })
+        .collect();
+    let range = |lines: &[usize]| match lines.first() {
+        Some(first) => format!("{},{}", first + 1, lines.len()),
+        None => "0,0".to_string(),
+    };
+    format!(
+        "@@ -{} +{} @@",
+        range(&expected_lines),
+        range(&actual_lines)
+    )
+}
+
+/// The first differing token of the first changed expected and actual line, as byte ranges within the lines
+struct Highlight {
+    expected_edit: Option<usize>,
+    expected: Option<(usize, usize)>,
+    actual_edit: Option<usize>,
+    actual: Option<(usize, usize)>,
+}
+
+fn first_difference(
+    edits: &[Edit],
+    expected_lines: &[&str],
+    actual_lines: &[&str],
+    flags: &ValidatorFlags,
+) -> Highlight {
+    // only look at the first block of changes, as a later line is not the first difference
+    let first_change = edits
+        .iter()
+        .position(|edit| !matches!(edit, Edit::Equal(..)))
+        .unwrap_or(edits.len());
+    let block_end = edits[first_change..]
+        .iter()
+        .position(|edit| matches!(edit, Edit::Equal(..)))
+        .map_or(edits.len(), |end| first_change + end);
+    let block = first_change..block_end;
+
+    let expected_edit = block.clone().find(|&k| matches!(edits[k], Edit::Delete(_)));
+    let actual_edit = block.clone().find(|&k| matches!(edits[k], Edit::Insert(_)));
+    let expected_line = match expected_edit.map(|k| edits[k]) {
+        Some(Edit::Delete(i)) => expected_lines[i],
+        _ => "",
+    };
+    let actual_line = match actual_edit.map(|k| edits[k]) {
+        Some(Edit::Insert(j)) => actual_lines[j],
+        _ => "",
+    };
+
+    let expected_tokens = token_ranges(expected_line);
+    let actual_tokens = token_ranges(actual_line);
+    let differing = (0..expected_tokens.len().max(actual_tokens.len())).find(|&k| {
+        match (expected_tokens.get(k), actual_tokens.get(k)) {
+            (Some(&(es, ee)), Some(&(as_, ae))) => {
+                !lines_match(&expected_line[es..ee], &actual_line[as_..ae], flags)
+            }
+            _ => true,
+        }
+    });
+
+    Highlight {
+        expected_edit,
+        expected: differing.and_then(|k| expected_tokens.get(k).copied()),
+        actual_edit,
+        actual: differing.and_then(|k| actual_tokens.get(k).copied()),
+    }
+}
+
+fn token_ranges(line: &str) -> Vec<(usize, usize)> {
+    line.split_whitespace()
+        .map(|token| {
+            let start = token.as_ptr() as usize - line.as_ptr() as usize;
+            (start, start + token.len())
+        })
+        .collect()
+}
+
+/// Colors a removed (`-`) or added (`+`) line, with the given byte range highlighted
+fn highlight_line(sign: char, line: &str, token: Option<(usize, usize)>) -> String {
+    let color = |text: &str| match sign {
+        '-' => text.red(),
+        _ => text.green(),
+    };
+    let Some((start, end)) = token.filter(|_| line.chars().count() <= MAX_LINE_LENGTH) else {
+        // a clipped line is not highlighted, but it is still clipped around the differing token
+        return color(&format!("{sign}{}", clip(line, token))).to_string();
+    };
+    let highlighted = match sign {
+        '-' => line[start..end].black().on_red(),
+        _ => line[start..end].black().on_green(),
+    };
+    format!(
+        "{}{}{}",
+        color(&format!("{sign}{}", &line[..start])),
+        highlighted,
+        color(&line[end..])
+    )
+}
+
+/// Shortens a line to at most `MAX_LINE_LENGTH` characters, keeping the given byte range in view
+fn clip(line: &str, focus: Option<(usize, usize)>) -> String {
+    let length = line.chars().count();
+    if length <= MAX_LINE_LENGTH {
+        return line.to_string();
+    }
+
+    let focus_start = focus.map_or(0, |(start, _)| line[..start].chars().count());
+    let start = focus_start.saturating_sub(MAX_LINE_LENGTH / 4);
+    let end = (start + MAX_LINE_LENGTH).min(length);
+    let mut clipped: String = line.chars().skip(start).take(end - start).collect();
+    if start > 0 {
+        clipped.insert(0, '…');
+    }
+    if end < length {
+        clipped.push_str(&format!("… ({} more characters)", length - end));
+    }
+    clipped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<&str> {
+        text.lines().collect()
+    }
+
+    fn plain_diff(expected: &str, actual: &str) -> String {
+        colored::control::set_override(false);
+        format_diff(expected, actual, &ValidatorFlags::default())
+    }
+
+    #[test]
+    fn identical_lines_are_equal() {
+        let (edits, aligned) = diff_lines(
+            &lines("1\n2\n3"),
+            &lines("1\n2\n3"),
+            &ValidatorFlags::default(),
+        );
+        assert_eq!(
+            edits,
+            vec![Edit::Equal(0, 0), Edit::Equal(1, 1), Edit::Equal(2, 2)]
+        );
+        assert!(aligned);
+        assert!(plain_diff("1 2\n3\n", "1  2\n3").contains("no lines differ"));
+    }
+
+    #[test]
+    fn finds_inserted_and_deleted_lines() {
+        let (edits, aligned) = diff_lines(
+            &lines("a\nb\nc\nd"),
+            &lines("a\nc\nd\ne"),
+            &ValidatorFlags::default(),
+        );
+        assert_eq!(
+            edits,
+            vec![
+                Edit::Equal(0, 0),
+                Edit::Delete(1),
+                Edit::Equal(2, 1),
+                Edit::Equal(3, 2),
+                Edit::Insert(3),
+            ]
+        );
+        assert!(aligned);
+
+        let diff = plain_diff("a\nb\nc\nd", "a\nc\nd\ne");
+        assert!(diff.contains("@@ -1,4 +1,4 @@"));
+        assert!(diff.contains("\n-b\n"));
+        assert!(diff.contains("\n+e\n"));
+    }
+
+    #[test]
+    fn compares_large_outputs_line_by_line() {
+        let expected: Vec<String> = (0..2001).map(|i| i.to_string()).collect();
+        let actual: Vec<String> = (1..2002).map(|i| i.to_string()).collect();
+        let expected: Vec<&str> = expected.iter().map(String::as_str).collect();
+        let actual: Vec<&str> = actual.iter().map(String::as_str).collect();
+
+        let (edits, aligned) = diff_lines(&expected, &actual, &ValidatorFlags::default());
+        assert!(!aligned);
+        // each line is compared to the line at the same position, even though the outputs are just shifted
+        assert_eq!(edits.len(), 2 * 2001);
+        assert_eq!(edits[..2], [Edit::Delete(0), Edit::Insert(0)]);
+    }
+
+    #[test]
+    fn truncates_long_diffs() {
+        let expected: String = (0..150).map(|i| format!("{i}\n")).collect();
+        let actual: String = (0..150).map(|i| format!("{}\n", i + 1000)).collect();
+        let diff = plain_diff(&expected, &actual);
+        assert!(diff.contains(
+            "… the diff was truncated after 100 lines - in total 150 of the 150 expected lines were removed and 150 of the 150 actual lines were added"
+        ));
+        assert_eq!(
+            diff.lines().filter(|line| line.starts_with('-')).count(),
+            100 + 1
+        );
+    }
+
+    #[test]
+    fn clips_long_lines_around_the_focus() {
+        assert_eq!(clip("short", None), "short");
+
+        let line = "a ".repeat(200) + "b";
+        let clipped = clip(&line, None);
+        assert!(clipped.starts_with("a a"));
+        assert!(clipped.ends_with("… (241 more characters)"));
+
+        let focus = line.len() - 1;
+        let clipped = clip(&line, Some((focus, focus + 1)));
+        assert!(clipped.starts_with('…'));
+        assert!(clipped.ends_with('b'));
+    }
+}
@@ -0,0 +1,31 @@
+use std::fmt;
+
+/// Errors that kat exits with a specific exit code for, so that scripts can tell what went wrong.
+/// All other errors exit with code 1.
+#[derive(Debug)]
+pub enum KatError {
+    /// Some tests failed, the reports of which have already been printed
+    TestsFailed,
+    /// A program could not be compiled, the output of the compiler has already been printed
+    CompileError { message: String, output: String },
+}
+
+impl KatError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            KatError::TestsFailed => 2,
+            KatError::CompileError { .. } => 3,
+        }
+    }
+}
+
+impl fmt::Display for KatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KatError::TestsFailed => write!(f, "❌ Some tests failed"),
+            KatError::CompileError { message, .. } => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for KatError {}
@@ -1,10 +1,10 @@
 use crate::App;
 
 use std::{
-    cmp::Ordering,
-    collections::HashMap,
     env::current_dir,
+    ffi::OsStr,
     fs,
+    ops::RangeInclusive,
     path::{Path, PathBuf},
 };
 
@@ -115,35 +115,177 @@ pub fn copy_template(
     Ok(())
 }
 
-fn parse_filter(filter: &str) -> Vec<u32> {
-    let mut test_numbers = Vec::new();
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    pub input: PathBuf,
+    /// The expected answer, which is missing for the sample interactions of interactive problems
+    pub answer: Option<PathBuf>,
+    /// The folder in the tests folder that the test is in, e.g. "custom", if it is not directly in the tests folder
+    pub group: Option<String>,
+}
 
-    for part in filter.split(',') {
-        if part.contains('-') {
-            let range_parts: Vec<&str> = part.split('-').collect();
-            let start: u32 = range_parts[0]
-                .trim()
-                .parse()
-                .expect("Invalid number in filter");
-            let end: u32 = range_parts[1]
+impl TestCase {
+    pub fn name(&self) -> String {
+        let file_name = self
+            .input
+            .file_name()
+            .expect("🙀 Failed to get file name from input file")
+            .to_string_lossy();
+        match &self.group {
+            Some(group) => format!("{group}/{file_name}"),
+            None => file_name.to_string(),
+        }
+    }
+
+    /// Whether the test is a sample interaction (a `.interaction` file) of an interactive problem
+    pub fn is_interaction(&self) -> bool {
+        self.input
+            .extension()
+            .is_some_and(|extension| extension == "interaction")
+    }
+}
+
+/// The folder in the tests folder for the tests that were added with `kat test add --custom`
+pub const CUSTOM_TEST_GROUP: &str = "custom";
+
+/// Saves a test in the tests folder of the problem (or in a group folder in it), numbered after all the existing tests,
+/// and returns the path of its input
+pub fn save_test(
+    problem_path: &Path,
+    test: &TestCase,
+    group: Option<&str>,
+) -> Result<PathBuf, Report> {
+    let test_path = problem_path.join("tests");
+    let last_number = last_test_number(&test_path);
+    let test_path = match group {
+        Some(group) => test_path.join(group),
+        None => test_path,
+    };
+    fs::create_dir_all(&test_path).wrap_err("🙀 Failed to create the tests folder")?;
+
+    let input_path = test_path.join(format!("{}.in", last_number + 1));
+    let answer_path = input_path.with_extension("ans");
+    fs::copy(&test.input, &input_path).wrap_err("🙀 Failed to save the test")?;
+    if let Some(answer) = &test.answer {
+        fs::copy(answer, &answer_path).wrap_err("🙀 Failed to save the test")?;
+    }
+    Ok(input_path)
+}
+
+/// The highest number in the names of the tests in a folder and its subfolders, or 0 if there are no numbered tests
+fn last_test_number(test_path: &Path) -> u32 {
+    let re = Regex::new(r"\d+").unwrap();
+    let Ok(entries) = fs::read_dir(test_path) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| {
+            if entry.path().is_dir() {
+                return last_test_number(&entry.path());
+            }
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            re.find(&file_name)
+                .and_then(|number| number.as_str().parse::<u32>().ok())
+                .unwrap_or(0)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// A part of the `--test-cases` selection, which is a comma separated list of these
+#[derive(Debug)]
+enum TestSelector {
+    /// Tests with a number in a range in their name, e.g. `3` or `1-3`
+    Numbers(RangeInclusive<u32>),
+    /// Tests whose name or file name (with or without extension) or group matches a pattern,
+    /// e.g. `secret/3.in`, `big`, `custom/*` or `sample`
+    Pattern(glob::Pattern),
+}
+
+impl TestSelector {
+    fn matches(&self, test: &TestCase, number: Option<u32>) -> bool {
+        match self {
+            TestSelector::Numbers(range) => number.is_some_and(|number| range.contains(&number)),
+            TestSelector::Pattern(pattern) => {
+                let name = test.name();
+                let file_name = test.input.file_name().unwrap_or_default();
+                [Path::new(&name), Path::new(file_name)].iter().any(|name| {
+                    pattern.matches_path(name) || pattern.matches_path(&name.with_extension(""))
+                }) || test.group.as_ref().is_some_and(|group| {
+                    Path::new(group)
+                        .ancestors()
+                        .any(|group| pattern.matches_path(group))
+                })
+            }
+        }
+    }
+}
+
+fn parse_filter(filter: &str) -> Result<Vec<TestSelector>, Report> {
+    let mut selectors = Vec::new();
+
+    for part in filter.split(',').map(str::trim) {
+        let numbers = match part.split_once('-') {
+            Some((start, end)) => start
                 .trim()
                 .parse()
-                .expect("Invalid number in filter");
-            test_numbers.extend(start..=end);
-        } else {
-            let number: u32 = part.trim().parse().expect("Invalid number in filter");
-            test_numbers.push(number);
-        }
+                .and_then(|start| Ok(start..=end.trim().parse()?)),
+            None => part.parse().map(|number| number..=number),
+        };
+        let selector = match numbers {
+            Ok(numbers) => TestSelector::Numbers(numbers),
+            Err(_) => TestSelector::Pattern(
+                glob::Pattern::new(part).wrap_err(format!("🙀 Invalid test selection: {part}"))?,
+            ),
+        };
+        selectors.push(selector);
+    }
+    Ok(selectors)
+}
+
+// The file in the .kat folder of a problem with the names of the tests that failed in the last run
+const FAILED_TESTS_FILE: &str = ".kat/failed_tests";
+
+/// The names of the tests that failed the last time the problem was tested, for `kat test --failed`
+pub fn read_failed_tests(problem_path: &Path) -> Vec<String> {
+    fs::read_to_string(problem_path.join(FAILED_TESTS_FILE))
+        .map(|names| names.lines().map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Remembers which tests failed, so that they can be run again with `kat test --failed`.
+/// Only the tests that were run are updated, so failures of tests outside e.g. `-t 3` are not forgotten.
+pub fn update_failed_tests(
+    problem_path: &Path,
+    passed: &[String],
+    failed: &[String],
+) -> Result<(), Report> {
+    let mut names = read_failed_tests(problem_path);
+    names.retain(|name| !passed.contains(name) && !failed.contains(name));
+    names.extend(failed.iter().cloned());
+
+    let path = problem_path.join(FAILED_TESTS_FILE);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).wrap_err("🙀 Failed to create the .kat folder")?;
     }
-    test_numbers
+    let names: String = names.iter().map(|name| format!("{name}\n")).collect();
+    fs::write(path, names).wrap_err("🙀 Failed to save the failed tests")
 }
 
+// The extensions of answer files, in order of preference if a test has more than one
+const ANSWER_EXTENSIONS: [&str; 2] = ["ans", "out"];
+
+/// Finds the tests of a problem in its tests folder and the folders in it (e.g. `sample` and `secret`), optionally only those
+/// in `test_cases`. Each input is paired with the answer file with the same name, i.e. `1.ans` (or `1.out`) for `1.in`.
+/// If `allow_missing_answers` is set, inputs without an answer file are tests without an answer, e.g. for `kat test --bless`
+/// to create their answers.
 pub fn find_test_files(
     _app: &App,
     test_cases: &Option<String>,
     problem_path: &Path,
-) -> Result<Vec<(PathBuf, PathBuf)>, Report> {
-    let extensions = ["in", "ans"];
+    allow_missing_answers: bool,
+) -> Result<Vec<TestCase>, Report> {
     let test_path = problem_path.join("tests");
     if !test_path.exists() {
         eyre::bail!("🙀 This problem does not have any tests - not testing!")
@@ -152,84 +294,254 @@ pub fn find_test_files(
         Some(filter) => filter,
         None => "all",
     };
-    let test_path = test_path
-        .to_str()
-        .expect("🙀 Failed to convert problem path to string")
-        .to_string();
 
-    let mut tests_by_ext = HashMap::new();
+    let mut files = Vec::new();
+    collect_test_files(&test_path, &mut files)
+        .wrap_err("🙀 Failed to read the tests of the problem")?;
+    let has_extension =
+        |file: &Path, extension: &str| file.extension().is_some_and(|ext| ext == extension);
+    let is_input = |file: &Path| has_extension(file, "in") || has_extension(file, "interaction");
+
+    let re = Regex::new(r"\d+").unwrap();
+    // tests without a number in their name can not be selected by number
+    let test_number = |test: &TestCase| -> Option<u32> {
+        re.find(&test.input.file_stem().unwrap_or_default().to_string_lossy())
+            .and_then(|number| number.as_str().parse().ok())
+    };
+    let test_filter = match filter {
+        "all" => None,
+        filter => Some(parse_filter(filter)?),
+    };
+    let mut tests: Vec<TestCase> = files
+        .iter()
+        .filter(|file| is_input(file))
+        .map(|input| TestCase {
+            // sample interactions are replayed against the program, so they do not need an answer
+            answer: (!has_extension(input, "interaction"))
+                .then(|| {
+                    ANSWER_EXTENSIONS
+                        .iter()
+                        .map(|extension| input.with_extension(extension))
+                        .find(|answer| answer.exists())
+                })
+                .flatten(),
+            group: test_group(&test_path, input),
+            input: input.clone(),
+        })
+        .filter(|test| {
+            test_filter.as_ref().is_none_or(|selectors| {
+                selectors
+                    .iter()
+                    .any(|selector| selector.matches(test, test_number(test)))
+            })
+        })
+        .collect();
+    tests.sort_by_cached_key(|test| {
+        let number = test_number(test);
+        (test.group.clone(), number.is_none(), number, test.name())
+    });
+
+    let unpaired_answers = find_unpaired_answers(&test_path, &files);
+    if !unpaired_answers.is_empty() {
+        eprintln!(
+            "{}",
+            format!(
+                "⚠️ Some answer files do not have an input file, so they are not tested: {}",
+                unpaired_answers.join(", ")
+            )
+            .bright_yellow()
+        );
+    }
 
-    for extension in extensions {
-        let pattern = format!("{test_path}/*.{}", &extension);
-        let files = glob(&pattern)
-            .expect("🙀 Failed to read glob pattern")
-            .filter_map(Result::ok)
-            .collect::<Vec<PathBuf>>();
+    let missing_answers: Vec<String> = tests
+        .iter()
+        .filter(|test| test.answer.is_none() && !test.is_interaction())
+        .map(|test| relative_test_path(&test_path, &test.input))
+        .collect();
+    if !missing_answers.is_empty() && !allow_missing_answers {
+        eyre::bail!(
+            "🙀 Some inputs do not have an answer file (.ans or .out): {}\nAdd their answers, or use --bless to save the output of your program as their answers.",
+            missing_answers.join(", ")
+        );
+    }
 
-        // glob seems to return files in alphabetical order -> rust-lang/glob/issues/98
-        // so no need for sorting
+    if tests.is_empty() {
+        match test_filter {
+            Some(_) => eyre::bail!("🙀 No tests match the test cases: {filter}"),
+            None => eyre::bail!(
+                "🙀 No input files found in directory: {}",
+                test_path.display()
+            ),
+        }
+    }
+    Ok(tests)
+}
+
+/// The answer files (with any of the answer extensions) that do not have an input file, so they are not tested
+fn find_unpaired_answers(test_path: &Path, files: &[PathBuf]) -> Vec<String> {
+    files
+        .iter()
+        .filter(|file| {
+            file.extension()
+                .is_some_and(|ext| ANSWER_EXTENSIONS.iter().any(|extension| ext == *extension))
+        })
+        .filter(|file| {
+            !file.with_extension("in").exists() && !file.with_extension("interaction").exists()
+        })
+        .map(|file| relative_test_path(test_path, file))
+        .collect()
+}
 
-        let matching_files = if filter == "all" {
-            files
+/// Collects the files in the tests folder and the folders in it, skipping hidden files and folders
+fn collect_test_files(folder: &Path, files: &mut Vec<PathBuf>) -> Result<(), Report> {
+    for entry in fs::read_dir(folder)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            collect_test_files(&path, files)?;
         } else {
-            let test_filter = parse_filter(filter);
-            files
-                .into_iter()
-                .filter(|file| {
-                    let file_name = file
-                        .file_stem()
-                        .expect("🙀 Failed to get file name from path")
-                        .to_str()
-                        .expect("🙀 Failed to convert file name to string");
-
-                    let re = Regex::new(r"\d+").unwrap();
-                    let number_part = re
-                        .find(file_name)
-                        .expect("🙀 No number found in file name")
-                        .as_str();
-                    let file_number: u32 = number_part
-                        .parse()
-                        .expect("🙀 File name does not contain a valid number");
-
-                    test_filter.contains(&file_number)
-                })
-                .collect::<Vec<PathBuf>>()
-        };
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The path of a test file relative to the tests folder, e.g. `secret/1.in`
+fn relative_test_path(test_path: &Path, file: &Path) -> String {
+    file.strip_prefix(test_path)
+        .unwrap_or(file)
+        .display()
+        .to_string()
+}
 
-        tests_by_ext.insert(extension, matching_files);
+/// The group of a test file, i.e. the folder it is in relative to the tests folder (e.g. `secret`),
+/// if that is not the tests folder itself
+fn test_group(test_path: &Path, file: &Path) -> Option<String> {
+    let folder = file.parent()?.strip_prefix(test_path).ok()?;
+    (!folder.as_os_str().is_empty()).then(|| folder.to_string_lossy().to_string())
+}
+
+/// The languages that have the extension of the file among their extensions, sorted by name
+fn languages_by_extension(app: &App, file_path: &Path) -> Vec<String> {
+    let Some(extension) = file_path
+        .extension()
+        .and_then(|extension| extension.to_str())
+    else {
+        return Vec::new();
+    };
+    let mut languages = app
+        .config
+        .kat_config
+        .languages
+        .iter()
+        .filter(|(_, language)| language.extensions.iter().any(|ext| ext == extension))
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<String>>();
+    // sort the languages, so that the same language is picked every time
+    languages.sort();
+    languages
+}
+
+/// Decides the language of a solution from the extension of its file.
+/// If several languages share the extension, the user is asked which one the solution is written in.
+pub fn choose_language_by_extension(app: &App, file_path: &Path) -> Result<String, Report> {
+    let languages = languages_by_extension(app, file_path);
+    match languages.as_slice() {
+        [] => eyre::bail!(
+            "🙀 Could not find any language with the extension of {} - specify the language with --language!",
+            file_path.display()
+        ),
+        [language] => Ok(language.clone()),
+        _ => {
+            let default_language = &app.config.kat_config.default.language;
+            let default = languages
+                .iter()
+                .position(|language| language == default_language)
+                .unwrap_or(0);
+            let language_choice = dialoguer::Select::new()
+                .with_prompt(format!(
+                    "👉 Multiple languages use the extension of {}, please choose which one to use:",
+                    file_path.display()
+                ))
+                .items(&languages)
+                .default(default)
+                .interact()
+                .wrap_err("🙀 Failed to get user input")?;
+            Ok(languages[language_choice].clone())
+        }
     }
+}
 
-    if let Some(in_files) = tests_by_ext.get("in") {
-        if let Some(ans_files) = tests_by_ext.get("ans") {
-            match in_files.len().cmp(&ans_files.len()) {
-                Ordering::Greater => {
-                    eyre::bail!("🙀 There are more input test files than answer files. Are they all in the {test_path} directory?");
-                }
-                Ordering::Less => {
-                    eyre::bail!("🙀 There are more answer files than input test files. Are they all in the {test_path} directory?");
-                }
-                Ordering::Equal => {
-                    if in_files.is_empty() || ans_files.is_empty() {
-                        let file_type = if in_files.is_empty() {
-                            "input"
-                        } else {
-                            "answer"
-                        };
-                        eyre::bail!("🙀 No {file_type} files found in directory: {test_path}");
-                    }
-                    let mut test_files = Vec::new();
-                    for (in_file, ans_file) in in_files.iter().zip(ans_files.iter()) {
-                        test_files.push((in_file.clone(), ans_file.clone()));
-                    }
-                    Ok(test_files)
-                }
-            }
-        } else {
-            eyre::bail!("🙀 No answer files found in directory: {}", test_path);
+pub fn find_output_validator(
+    app: &App,
+    problem_path: &Path,
+    validator: &Option<String>,
+) -> Result<Option<PathBuf>, Report> {
+    if let Some(validator) = validator {
+        let validator_path = problem_path.join(validator);
+        if !validator_path.exists() {
+            eyre::bail!(
+                "🙀 The output validator {} does not exist!",
+                validator_path.display()
+            );
         }
-    } else {
-        eyre::bail!("🙀 No input files found in directory: {}", test_path);
+        return Ok(Some(validator_path));
     }
+
+    // otherwise look for a file named e.g., validator.cpp or output_validator.py in the problem directory
+    Ok(find_helper_program(
+        app,
+        problem_path,
+        &["validator", "output_validator"],
+    ))
+}
+
+/// Finds a program in the problem directory with one of the given names (without extension),
+/// e.g., `generator.py` for the name `generator`, ignoring files in languages that are not configured
+pub fn find_helper_program(app: &App, problem_path: &Path, names: &[&str]) -> Option<PathBuf> {
+    let problem_path = problem_path
+        .to_str()
+        .expect("🙀 Failed to convert problem path to string")
+        .to_string();
+    names.iter().find_map(|name| {
+        let pattern = format!("{problem_path}/{name}.*");
+        glob(&pattern)
+            .expect("🙀 Failed to read glob pattern")
+            .filter_map(Result::ok)
+            .find(|file| !languages_by_extension(app, file).is_empty())
+    })
+}
+
+// The build directory used when neither the problem config nor the config file specifies one
+const DEFAULT_BUILD_DIRECTORY: &str = ".kat/build";
+
+/// Finds the directory where the compiled files of a problem are placed, from the problem config or the config file.
+/// The `{problem}` placeholder is replaced by the problem id, and relative paths are relative to the problem directory.
+pub fn find_build_directory(
+    app: &App,
+    problem_path: &Path,
+    build_directory: &Option<String>,
+) -> Result<PathBuf, Report> {
+    let build_directory = build_directory
+        .as_deref()
+        .or(app.config.kat_config.default.build_directory.as_deref())
+        .unwrap_or(DEFAULT_BUILD_DIRECTORY);
+
+    let problem_id = problem_path
+        .file_name()
+        .wrap_err("🙀 Failed to get file name from path")?
+        .to_string_lossy();
+    let build_directory = build_directory.replace("{problem}", &problem_id);
+    let build_directory = shellexpand::full(&build_directory)
+        .wrap_err("🙀 Failed to expand the build directory")?
+        .to_string();
+
+    Ok(problem_path.join(build_directory))
 }
 
 pub fn find_problem_files(
@@ -256,6 +568,33 @@ pub fn find_problem_files(
     matching_files
 }
 
+/// Finds all solutions of the problem in any of the configured languages, together with their language
+pub fn find_solution_files(
+    app: &App,
+    problem_path: &Path,
+    problem_id: &str,
+) -> Result<Vec<(PathBuf, String)>, Report> {
+    let mut extensions: Vec<String> = app
+        .config
+        .kat_config
+        .languages
+        .values()
+        .flat_map(|language| language.extensions.clone())
+        .collect();
+    extensions.sort();
+    extensions.dedup();
+
+    let mut files = find_problem_files(problem_path, problem_id, &extensions);
+    files.sort();
+    files
+        .into_iter()
+        .map(|file| {
+            let language = choose_language_by_extension(app, &file)?;
+            Ok((file, language))
+        })
+        .collect()
+}
+
 pub fn get_problem_file(
     app: &App,
     file: &Option<PathBuf>,
@@ -276,15 +615,17 @@ pub fn get_problem_file(
         .bright_blue()
     );
     let config = &app.config.kat_config;
-    let language = match &language {
-        Some(lang) => {
+    // a language given on the command line always wins, otherwise the language of a given file is decided by its extension
+    let language = match (&language, &file) {
+        (Some(lang), _) => {
             if config.languages.contains_key(lang) {
-                lang
+                lang.clone()
             } else {
                 eyre::bail!("🙀 Invalid language: {}", lang);
             }
         }
-        None => &config.default.language,
+        (None, Some(problem_file_path)) => choose_language_by_extension(app, problem_file_path)?,
+        (None, None) => config.default.language.clone(),
     };
     let (problem_file, problem_file_path) = match &file {
         Some(problem_file_path) => (
@@ -299,7 +640,7 @@ pub fn get_problem_file(
         None => {
             let extensions = config
                 .languages
-                .get(language)
+                .get(&language)
                 .wrap_err(format!(
                     "🙀 Could not find any language with the name: {}",
                     language
@@ -352,12 +693,12 @@ pub fn get_problem_file(
             }
         }
     };
-    Ok((problem_file, problem_file_path, language.to_string()))
+    Ok((problem_file, problem_file_path, language))
 }
 
 pub fn find_problem_dir(_app: &App, path: &Path) -> Result<(PathBuf, String), Report> {
     let current_dir = current_dir().wrap_err("🙀 Failed to get current directory")?;
-    if path == PathBuf::from(".") {
+    if path.as_os_str() == OsStr::new(".") {
         let problem_id = current_dir
             .file_name()
             .expect("🙀 Failed to get file name from path")
@@ -405,3 +746,34 @@ pub fn find_problem_dir(_app: &App, path: &Path) -> Result<(PathBuf, String), Re
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn warns_about_answers_of_every_extension_without_an_input() {
+        let problem = tempfile::tempdir().unwrap();
+        let test_path = problem.path().join("tests");
+        fs::create_dir_all(test_path.join("secret")).unwrap();
+        for file in [
+            "1.in",
+            "1.ans",
+            "2.in",
+            "2.out",
+            "3.interaction",
+            "3.ans",
+            "lone.ans",
+            "lone.out",
+            "secret/big.out",
+        ] {
+            fs::write(test_path.join(file), "").unwrap();
+        }
+
+        let mut files = Vec::new();
+        collect_test_files(&test_path, &mut files).unwrap();
+        let mut unpaired = find_unpaired_answers(&test_path, &files);
+        unpaired.sort();
+        assert_eq!(unpaired, ["lone.ans", "lone.out", "secret/big.out"]);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::utils::{
+    process::{Direction, Transcript},
+    validator::{validate_output, Judgement, ValidatorFlags},
+};
+
+use std::io::{BufRead, BufReader, Read, Write};
+
+/// Plays a sample interaction (a `.interaction` file) against a program, acting as a simple interactor.
+/// Lines starting with `>` are sent to the program, and lines starting with `<` are expected from the program.
+pub fn play_interaction(
+    interaction: &str,
+    mut stdin: impl Write,
+    stdout: impl Read,
+    flags: &ValidatorFlags,
+) -> (Judgement, Transcript) {
+    let mut transcript = Transcript::new();
+    let mut stdout = BufReader::new(stdout);
+
+    for (line_number, line) in interaction.lines().enumerate() {
+        if let Some(message) = line.strip_prefix('>') {
+            let message = format!("{message}\n");
+            transcript.push((Direction::ToProgram, message.clone().into_bytes()));
+            if stdin
+                .write_all(message.as_bytes())
+                .and_then(|_| stdin.flush())
+                .is_err()
+            {
+                return (
+                    Judgement::WrongAnswer(format!(
+                        "the program stopped reading input before line {} of the interaction",
+                        line_number + 1
+                    )),
+                    transcript,
+                );
+            }
+        } else if let Some(expected) = line.strip_prefix('<') {
+            let mut received = String::new();
+            if stdout.read_line(&mut received).unwrap_or(0) == 0 {
+                return (
+                    Judgement::WrongAnswer(format!(
+                        "expected `{}` on line {} of the interaction, but the output ended",
+                        expected.trim(),
+                        line_number + 1
+                    )),
+                    transcript,
+                );
+            }
+            transcript.push((Direction::FromProgram, received.clone().into_bytes()));
+            if let Judgement::WrongAnswer(_) = validate_output(expected, &received, flags) {
+                return (
+                    Judgement::WrongAnswer(format!(
+                        "expected `{}` but got `{}` on line {} of the interaction",
+                        expected.trim(),
+                        received.trim(),
+                        line_number + 1
+                    )),
+                    transcript,
+                );
+            }
+        }
+    }
+
+    // the interaction is over, so close stdin and make sure that the program does not print anything else
+    drop(stdin);
+    let mut rest = String::new();
+    let _ = stdout.read_to_string(&mut rest);
+    if !rest.is_empty() {
+        transcript.push((Direction::FromProgram, rest.clone().into_bytes()));
+    }
+    match validate_output("", &rest, flags) {
+        Judgement::Accepted => (Judgement::Accepted, transcript),
+        Judgement::WrongAnswer(reason) => (
+            Judgement::WrongAnswer(format!("{reason} after the interaction ended")),
+            transcript,
+        ),
+    }
+}
+
+/// Formats a transcript in the same format as the `.interaction` files,
+/// i.e. with `>` in front of what was sent to the program and `<` in front of what the program sent
+pub fn format_transcript(transcript: &Transcript) -> String {
+    let mut formatted = String::new();
+    let mut current: Option<(Direction, Vec<u8>)> = None;
+
+    // merge the chunks that were sent in the same direction, so that they can be split into lines
+    for (direction, chunk) in transcript {
+        match &mut current {
+            Some((current_direction, data)) if current_direction == direction => {
+                data.extend_from_slice(chunk)
+            }
+            _ => {
+                if let Some((direction, data)) = current.take() {
+                    push_lines(&mut formatted, direction, &data);
+                }
+                current = Some((*direction, chunk.clone()));
+            }
+        }
+    }
+    if let Some((direction, data)) = current {
+        push_lines(&mut formatted, direction, &data);
+    }
+
+    formatted
+}
+
+fn push_lines(formatted: &mut String, direction: Direction, data: &[u8]) {
+    let prefix = match direction {
+        Direction::ToProgram => '>',
+        Direction::FromProgram => '<',
+    };
+    for line in String::from_utf8_lossy(data).lines() {
+        formatted.push(prefix);
+        formatted.push_str(line);
+        formatted.push('\n');
+    }
+}
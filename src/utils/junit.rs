@@ -0,0 +1,131 @@
+use color_eyre::{eyre::Context, Report};
+use regex::Regex;
+
+use std::{fmt::Write, fs, path::Path};
+
+/// A problem that was tested, as a test suite in a JUnit XML report
+#[derive(Debug)]
+pub struct JunitSuite {
+    pub name: String,
+    /// The time it took to run all of the tests, in seconds
+    pub time: f64,
+    pub cases: Vec<JunitCase>,
+}
+
+/// A single test of a problem, as a test case in a JUnit XML report
+#[derive(Debug)]
+pub struct JunitCase {
+    pub name: String,
+    /// The time the test ran for, in seconds
+    pub time: f64,
+    pub outcome: JunitOutcome,
+}
+
+#[derive(Debug)]
+pub enum JunitOutcome {
+    Passed,
+    /// The program gave a wrong verdict, e.g. a wrong answer or a time limit exceeded
+    Failure {
+        kind: String,
+        message: String,
+        details: String,
+    },
+    /// The test could not be run at all, e.g. because the problem failed to compile
+    Error {
+        message: String,
+        details: String,
+    },
+}
+
+/// Writes the results of a problem to a JUnit XML report, which CI systems can show per test
+pub fn write_junit_report(path: &Path, suite: &JunitSuite) -> Result<(), Report> {
+    let failures = suite
+        .cases
+        .iter()
+        .filter(|case| matches!(case.outcome, JunitOutcome::Failure { .. }))
+        .count();
+    let errors = suite
+        .cases
+        .iter()
+        .filter(|case| matches!(case.outcome, JunitOutcome::Error { .. }))
+        .count();
+    let attributes = format!(
+        r#"name="{}" tests="{}" failures="{failures}" errors="{errors}" time="{:.3}""#,
+        escape(&suite.name),
+        suite.cases.len(),
+        suite.time
+    );
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(xml, "<testsuites {attributes}>");
+    let _ = writeln!(xml, "  <testsuite {attributes}>");
+    for case in &suite.cases {
+        let _ = write!(
+            xml,
+            r#"    <testcase name="{}" classname="{}" time="{:.3}""#,
+            escape(&case.name),
+            escape(&suite.name),
+            case.time
+        );
+        match &case.outcome {
+            JunitOutcome::Passed => xml.push_str("/>\n"),
+            JunitOutcome::Failure {
+                kind,
+                message,
+                details,
+            } => {
+                let _ = writeln!(
+                    xml,
+                    ">\n      <failure type=\"{}\" message=\"{}\">{}</failure>\n    </testcase>",
+                    escape(kind),
+                    escape(message),
+                    escape(&strip_colors(details))
+                );
+            }
+            JunitOutcome::Error { message, details } => {
+                let _ = writeln!(
+                    xml,
+                    ">\n      <error message=\"{}\">{}</error>\n    </testcase>",
+                    escape(message),
+                    escape(&strip_colors(details))
+                );
+            }
+        }
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).wrap_err("🙀 Failed to create the report directory")?;
+    }
+    fs::write(path, xml).wrap_err(format!(
+        "🙀 Failed to write the JUnit report to {}",
+        path.display()
+    ))
+}
+
+/// Removes the terminal colors from the reports, as they are meant for a terminal and not a CI system
+fn strip_colors(text: &str) -> String {
+    let re = Regex::new(r"\x1b\[[0-9;]*m").unwrap();
+    re.replace_all(text, "").into_owned()
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // XML does not allow most control characters, even when escaped
+            '\n' | '\r' | '\t' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
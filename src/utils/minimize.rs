@@ -0,0 +1,276 @@
+use crate::cli::ShrinkStrategy;
+
+use std::ops::Range;
+
+use color_eyre::Report;
+
+/// A way of splitting a test input into parts that can be removed while minimizing it
+pub trait Shrinker {
+    fn name(&self) -> &'static str;
+    /// The number of parts of the input that can be removed, or `None` if the input does not have the format of the shrinker
+    fn parts(&self, input: &str) -> Option<usize>;
+    /// The input with the parts in the given range removed
+    fn remove(&self, input: &str, parts: Range<usize>) -> String;
+}
+
+/// Removes whole lines
+pub struct Lines;
+
+/// Removes single tokens (separated by whitespace) from any line, and the lines that end up empty
+pub struct Tokens;
+
+/// Removes items from inputs where the first line is the number of items N, followed by N lines with an item each,
+/// and updates N to match
+pub struct CountedLines;
+
+/// Removes items from inputs where the first line is the number of items N, followed by a line with N items,
+/// and updates N to match
+pub struct CountedTokens;
+
+impl Shrinker for Lines {
+    fn name(&self) -> &'static str {
+        "lines"
+    }
+
+    fn parts(&self, input: &str) -> Option<usize> {
+        Some(input.lines().count())
+    }
+
+    fn remove(&self, input: &str, parts: Range<usize>) -> String {
+        join_lines(
+            input
+                .lines()
+                .enumerate()
+                .filter(|(i, _)| !parts.contains(i))
+                .map(|(_, line)| line.to_string()),
+        )
+    }
+}
+
+impl Shrinker for Tokens {
+    fn name(&self) -> &'static str {
+        "tokens"
+    }
+
+    fn parts(&self, input: &str) -> Option<usize> {
+        Some(input.split_whitespace().count())
+    }
+
+    fn remove(&self, input: &str, parts: Range<usize>) -> String {
+        let mut index = 0;
+        join_lines(input.lines().filter_map(|line| {
+            let tokens: Vec<&str> = line
+                .split_whitespace()
+                .filter(|_| {
+                    index += 1;
+                    !parts.contains(&(index - 1))
+                })
+                .collect();
+            (!tokens.is_empty()).then(|| tokens.join(" "))
+        }))
+    }
+}
+
+impl Shrinker for CountedLines {
+    fn name(&self) -> &'static str {
+        "counted lines"
+    }
+
+    fn parts(&self, input: &str) -> Option<usize> {
+        let count = item_count(input)?;
+        (input.lines().count() > count).then_some(count)
+    }
+
+    fn remove(&self, input: &str, parts: Range<usize>) -> String {
+        let count = item_count(input).unwrap_or_default();
+        let removed = parts.len().min(count);
+        let lines = input
+            .lines()
+            .skip(1)
+            .enumerate()
+            .filter(|(i, _)| *i >= count || !parts.contains(i))
+            .map(|(_, line)| line.to_string());
+        join_lines(std::iter::once((count - removed).to_string()).chain(lines))
+    }
+}
+
+impl Shrinker for CountedTokens {
+    fn name(&self) -> &'static str {
+        "counted tokens"
+    }
+
+    fn parts(&self, input: &str) -> Option<usize> {
+        let count = item_count(input)?;
+        let items = input.lines().nth(1)?.split_whitespace().count();
+        (items == count).then_some(count)
+    }
+
+    fn remove(&self, input: &str, parts: Range<usize>) -> String {
+        let mut lines = input.lines();
+        lines.next();
+        let items: Vec<&str> = lines
+            .next()
+            .unwrap_or_default()
+            .split_whitespace()
+            .enumerate()
+            .filter(|(i, _)| !parts.contains(i))
+            .map(|(_, item)| item)
+            .collect();
+        join_lines(
+            [items.len().to_string(), items.join(" ")]
+                .into_iter()
+                .chain(lines.map(str::to_string)),
+        )
+    }
+}
+
+/// The shrinkers to minimize an input with, in the order they are tried
+pub fn shrinkers(strategy: ShrinkStrategy) -> Vec<Box<dyn Shrinker>> {
+    match strategy {
+        ShrinkStrategy::Auto => vec![
+            Box::new(CountedLines),
+            Box::new(CountedTokens),
+            Box::new(Lines),
+            Box::new(Tokens),
+        ],
+        ShrinkStrategy::Counted => vec![Box::new(CountedLines), Box::new(CountedTokens)],
+        ShrinkStrategy::Lines => vec![Box::new(Lines)],
+        ShrinkStrategy::Tokens => vec![Box::new(Tokens)],
+    }
+}
+
+/// The number on the first line of the input, if that is all the first line contains
+fn item_count(input: &str) -> Option<usize> {
+    input.lines().next()?.trim().parse().ok()
+}
+
+fn join_lines(lines: impl Iterator<Item = String>) -> String {
+    lines.map(|line| line + "\n").collect()
+}
+
+/// Minimizes an input with delta debugging: parts of the input are removed with each shrinker, first in large chunks
+/// and then in smaller ones, as long as `still_fails` says the smaller input still makes the program fail.
+/// This repeats until no single part can be removed any more.
+pub fn minimize(
+    input: String,
+    shrinkers: &[Box<dyn Shrinker>],
+    mut still_fails: impl FnMut(&str) -> Result<bool, Report>,
+) -> Result<String, Report> {
+    let mut input = input;
+    loop {
+        let size = input.len();
+        for shrinker in shrinkers {
+            input = shrink(input, shrinker.as_ref(), &mut still_fails)?;
+        }
+        if input.len() == size {
+            return Ok(input);
+        }
+    }
+}
+
+fn shrink(
+    mut input: String,
+    shrinker: &dyn Shrinker,
+    still_fails: &mut impl FnMut(&str) -> Result<bool, Report>,
+) -> Result<String, Report> {
+    let Some(mut parts) = shrinker.parts(&input) else {
+        return Ok(input);
+    };
+    log::info!("Removing {} from the input", shrinker.name());
+    let mut chunk_size = (parts / 2).max(1);
+    while parts > 0 {
+        let mut removed_any = false;
+        let mut start = 0;
+        while start < parts {
+            let end = (start + chunk_size).min(parts);
+            let candidate = shrinker.remove(&input, start..end);
+            if candidate != input && still_fails(&candidate)? {
+                input = candidate;
+                removed_any = true;
+                match shrinker.parts(&input) {
+                    Some(remaining) => parts = remaining,
+                    None => return Ok(input),
+                }
+            } else {
+                start = end;
+            }
+        }
+        if !removed_any {
+            if chunk_size == 1 {
+                break;
+            }
+            chunk_size /= 2;
+        }
+        chunk_size = chunk_size.min(parts).max(1);
+    }
+    Ok(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minimize_with(
+        input: &str,
+        strategy: ShrinkStrategy,
+        still_fails: impl Fn(&str) -> bool,
+    ) -> String {
+        minimize(input.to_string(), &shrinkers(strategy), |input| {
+            Ok(still_fails(input))
+        })
+        .unwrap()
+    }
+
+    /// Whether the count on the first line matches the number of lines after it
+    fn count_matches(input: &str) -> bool {
+        item_count(input) == Some(input.lines().count() - 1)
+    }
+
+    #[test]
+    fn counted_shrinkers_update_the_count() {
+        assert_eq!(CountedLines.parts("3\na\nb\nc\nrest\n"), Some(3));
+        assert_eq!(CountedLines.parts("3\na\nb\n"), None);
+        assert_eq!(
+            CountedLines.remove("3\na\nb\nc\nrest\n", 0..2),
+            "1\nc\nrest\n"
+        );
+
+        assert_eq!(CountedTokens.parts("4\n1 2 3 4\nrest\n"), Some(4));
+        assert_eq!(CountedTokens.parts("4\n1 2 3\n"), None);
+        assert_eq!(
+            CountedTokens.remove("4\n1 2 3 4\nrest\n", 1..3),
+            "2\n1 4\nrest\n"
+        );
+    }
+
+    #[test]
+    fn counted_input_keeps_a_consistent_count() {
+        let input = "6\n5\n1\n7\n3\n9\n2\n";
+        let minimized = minimize_with(input, ShrinkStrategy::Auto, |input| {
+            count_matches(input) && input.lines().skip(1).any(|line| line == "7")
+        });
+        assert_eq!(minimized, "1\n7\n");
+    }
+
+    #[test]
+    fn stops_at_the_smallest_failing_input() {
+        let input = "a\nb\nc\nd\ne\nf\ng\n";
+        let minimized = minimize_with(input, ShrinkStrategy::Lines, |input| {
+            input.contains('b') && input.contains('f')
+        });
+        assert_eq!(minimized, "b\nf\n");
+
+        let input = "1 2 3\n4 5 6\n7 8\n";
+        let minimized = minimize_with(input, ShrinkStrategy::Tokens, |input| {
+            input.split_whitespace().any(|token| token == "5")
+        });
+        assert_eq!(minimized, "5\n");
+    }
+
+    #[test]
+    fn keeps_an_input_that_can_not_be_shrunk() {
+        let input = "a\nb\n";
+        let minimized = minimize_with(input, ShrinkStrategy::Lines, |input| input == "a\nb\n");
+        assert_eq!(minimized, input);
+    }
+}
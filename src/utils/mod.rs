@@ -1,5 +1,15 @@
+pub mod build_cache;
 pub mod config;
+pub mod diff;
+pub mod errors;
 pub mod fileutils;
+pub mod interaction;
+pub mod junit;
+pub mod minimize;
+pub mod pager;
+pub mod process;
+pub mod validator;
+pub mod verdict;
 pub mod webutils;
 
 pub use config::AppConfig;
@@ -0,0 +1,49 @@
+use std::{
+    env,
+    io::{self, IsTerminal, Write},
+    process::{Command, Stdio},
+};
+
+/// Prints a report, sending it through the pager in `$PAGER` (or `less`) if asked to,
+/// and the report does not fit in the terminal
+pub fn print_report(report: &str, use_pager: bool) {
+    if use_pager && io::stdout().is_terminal() && !fits_in_terminal(report) {
+        match page(report) {
+            Ok(()) => return,
+            Err(e) => log::warn!("Could not start the pager: {e}"),
+        }
+    }
+    println!("{report}");
+}
+
+fn fits_in_terminal(report: &str) -> bool {
+    match termion::terminal_size() {
+        Ok((_, rows)) => report.lines().count() < rows as usize,
+        Err(_) => true,
+    }
+}
+
+fn page(report: &str) -> io::Result<()> {
+    let pager = env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string());
+
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(&pager).stdin(Stdio::piped());
+    // like git, let less show the colors, and quit right away if the report fits on the screen after all
+    if env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+
+    let mut child = command.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // the pager closing its input early just means that the user quit it
+        match writeln!(stdin, "{report}") {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
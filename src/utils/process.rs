@@ -0,0 +1,473 @@
+use std::{
+    io::{self, Read, Write},
+    os::unix::process::{CommandExt, ExitStatusExt},
+    process::{ChildStdin, ChildStdout, Command, ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// How much longer than the (CPU) time limit a program is allowed to run in wall-clock time before it is killed.
+// This gives programs that are blocked on e.g., I/O a bit of slack, while still making sure that they terminate.
+const WALL_TIME_FACTOR: f64 = 2.0;
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type Resource = libc::__rlimit_resource_t;
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+type Resource = libc::c_int;
+
+// Messages that programs in common languages print when they fail to allocate memory
+const OUT_OF_MEMORY_MESSAGES: [&str; 5] = [
+    "std::bad_alloc",
+    "MemoryError",
+    "memory allocation of",
+    "OutOfMemoryError",
+    "Cannot allocate memory",
+];
+
+#[derive(Debug, Clone)]
+pub struct Limits {
+    pub time_limit: Duration,
+    /// The memory limit in bytes
+    pub memory_limit: Option<u64>,
+    /// Whether the memory limit should also be enforced as a limit on the address space of the process.
+    /// This does not work well for languages with a runtime that reserves a lot of memory up front, e.g., Java.
+    pub limit_address_space: bool,
+    /// The stack size in bytes, raised from the default of usually 8MB as Kattis allows deep recursion
+    pub stack_size: Option<u64>,
+    /// The maximum number of files the process can have open at the same time
+    pub open_files: Option<u64>,
+    /// The maximum number of bytes the process can write to stdout, before it is killed
+    pub output_limit: Option<u64>,
+}
+
+impl Limits {
+    pub fn wall_time_limit(&self) -> Duration {
+        self.time_limit.mul_f64(WALL_TIME_FACTOR)
+    }
+}
+
+#[derive(Debug)]
+pub struct ProcessOutput {
+    pub status: ExitStatus,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub wall_time: Duration,
+    pub user_time: Duration,
+    pub system_time: Duration,
+    /// The peak resident set size of the process in bytes
+    pub peak_memory: u64,
+    pub timed_out: bool,
+    /// Whether the process was killed for writing more than the output limit, in which case stdout is cut off
+    pub exceeded_output_limit: bool,
+}
+
+impl ProcessOutput {
+    pub fn cpu_time(&self) -> Duration {
+        self.user_time + self.system_time
+    }
+
+    pub fn exceeded_time_limit(&self, limits: &Limits) -> bool {
+        self.timed_out
+            || self.cpu_time() > limits.time_limit
+            || self.status.signal() == Some(libc::SIGXCPU)
+    }
+
+    pub fn exceeded_memory_limit(&self, limits: &Limits) -> bool {
+        let Some(memory_limit) = limits.memory_limit else {
+            return false;
+        };
+        if self.peak_memory > memory_limit {
+            return true;
+        }
+        // when the address space is limited, allocations fail before the peak memory reaches the limit,
+        // so we have to look at how the program failed instead
+        let stderr = String::from_utf8_lossy(&self.stderr);
+        !self.status.success()
+            && OUT_OF_MEMORY_MESSAGES
+                .iter()
+                .any(|message| stderr.contains(message))
+    }
+}
+
+/// Runs the command with the given stdin, while enforcing the time and memory limits in `limits`.
+/// The command is started in its own process group, so that the whole process tree can be killed
+/// if the command runs for too long.
+pub fn run_limited(
+    command: &mut Command,
+    stdin: Stdio,
+    limits: &Limits,
+) -> Result<ProcessOutput, io::Error> {
+    spawn_limited(command, stdin, limits)?.wait()
+}
+
+/// A running process started with [`spawn_limited`]. The stdin and stdout of the process can be taken to
+/// communicate with it, otherwise the stdout is collected when waiting for the process.
+pub struct LimitedChild {
+    pub stdin: Option<ChildStdin>,
+    stdout: Option<ChildStdout>,
+    pid: libc::pid_t,
+    start_time: Instant,
+    wall_time_limit: Duration,
+    output_limit: Option<u64>,
+    exceeded_output_limit: Arc<AtomicBool>,
+    stderr_reader: JoinHandle<Vec<u8>>,
+}
+
+/// The stdout of a [`LimitedChild`] that is read while the process runs, e.g., to relay it to an interactor.
+/// The process is killed once it writes more than the output limit, and reading then stops at the limit.
+pub struct LimitedStdout {
+    stdout: ChildStdout,
+    pid: libc::pid_t,
+    remaining: Option<u64>,
+    exceeded_output_limit: Arc<AtomicBool>,
+}
+
+pub fn spawn_limited(
+    command: &mut Command,
+    stdin: Stdio,
+    limits: &Limits,
+) -> Result<LimitedChild, io::Error> {
+    // the CPU limit is only a backstop - the verdict is decided from the measured CPU time
+    let cpu_limit = limits.time_limit.as_secs() + 1;
+    let address_space_limit = limits
+        .memory_limit
+        .filter(|_| limits.limit_address_space && cfg!(target_os = "linux"));
+    let stack_size = limits.stack_size;
+    let open_files = limits.open_files;
+
+    command
+        .stdin(stdin)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0);
+
+    // SAFETY: getrlimit and setrlimit are async-signal-safe, and we do not allocate in the closure
+    unsafe {
+        command.pre_exec(move || {
+            set_limit(libc::RLIMIT_CPU, cpu_limit)?;
+            if let Some(address_space_limit) = address_space_limit {
+                set_limit(libc::RLIMIT_AS, address_space_limit)?;
+            }
+            if let Some(stack_size) = stack_size {
+                set_soft_limit(libc::RLIMIT_STACK, stack_size)?;
+            }
+            if let Some(open_files) = open_files {
+                set_soft_limit(libc::RLIMIT_NOFILE, open_files)?;
+            }
+            Ok(())
+        });
+    }
+
+    let start_time = Instant::now();
+    let mut child = command.spawn()?;
+
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let stderr_reader = thread::spawn(move || read_all(stderr));
+
+    Ok(LimitedChild {
+        stdin: child.stdin.take(),
+        stdout: child.stdout.take(),
+        pid: child.id() as libc::pid_t,
+        start_time,
+        wall_time_limit: limits.wall_time_limit(),
+        output_limit: limits.output_limit,
+        exceeded_output_limit: Arc::new(AtomicBool::new(false)),
+        stderr_reader,
+    })
+}
+
+impl LimitedChild {
+    /// Takes the stdout of the process to read it while the process runs, with the output limit still enforced
+    pub fn take_stdout(&mut self) -> Option<LimitedStdout> {
+        self.stdout.take().map(|stdout| LimitedStdout {
+            stdout,
+            pid: self.pid,
+            remaining: self.output_limit,
+            exceeded_output_limit: Arc::clone(&self.exceeded_output_limit),
+        })
+    }
+
+    /// Waits for the process to finish, and kills it (and all of its children) if it exceeds the wall time limit
+    pub fn wait(mut self) -> Result<ProcessOutput, io::Error> {
+        // close stdin if it was not taken, so that the process does not wait for input forever
+        drop(self.stdin.take());
+        let (pid, output_limit) = (self.pid, self.output_limit);
+        let stdout_reader = self.stdout.take().map(|stdout| {
+            thread::spawn(move || {
+                let (stdout, exceeded) = read_limited(stdout, output_limit);
+                if exceeded {
+                    kill_process_group(pid);
+                }
+                (stdout, exceeded)
+            })
+        });
+
+        let mut timed_out = false;
+        let (status, rusage) = loop {
+            if let Some(result) = try_wait(self.pid)? {
+                break result;
+            }
+            if !timed_out && self.start_time.elapsed() > self.wall_time_limit {
+                timed_out = true;
+                kill_process_group(self.pid);
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+        let wall_time = self.start_time.elapsed();
+
+        // make sure that no processes spawned by the program are left behind holding on to the pipes
+        kill_process_group(self.pid);
+
+        let (stdout, exceeded_output_limit) = stdout_reader
+            .map(|reader| reader.join().unwrap_or_default())
+            .unwrap_or_default();
+        let exceeded_output_limit =
+            exceeded_output_limit || self.exceeded_output_limit.load(Ordering::SeqCst);
+        let stderr = self.stderr_reader.join().unwrap_or_default();
+
+        Ok(ProcessOutput {
+            status,
+            stdout,
+            stderr,
+            wall_time,
+            user_time: timeval_to_duration(rusage.ru_utime),
+            system_time: timeval_to_duration(rusage.ru_stime),
+            peak_memory: max_rss_to_bytes(rusage.ru_maxrss),
+            timed_out,
+            exceeded_output_limit,
+        })
+    }
+}
+
+impl Read for LimitedStdout {
+    fn read(&mut self, buffer: &mut [u8]) -> Result<usize, io::Error> {
+        let Some(remaining) = self.remaining else {
+            return self.stdout.read(buffer);
+        };
+        if self.exceeded_output_limit.load(Ordering::SeqCst) {
+            return Ok(0);
+        }
+        let read = self.stdout.read(buffer)?;
+        if read as u64 > remaining {
+            self.exceeded_output_limit.store(true, Ordering::SeqCst);
+            kill_process_group(self.pid);
+            return Ok(remaining as usize);
+        }
+        self.remaining = Some(remaining - read as u64);
+        Ok(read)
+    }
+}
+
+/// Runs the command with the given stdin, and with its stdout and stderr connected to those of kat, so that
+/// the output is shown while it runs. Only the stack size and the number of open files are limited, so that
+/// the program behaves like when it is tested, while it can still run (or wait for input) as long as it likes.
+/// The time and memory usage is still measured.
+pub fn run_attached(
+    command: &mut Command,
+    stdin: Stdio,
+    limits: &Limits,
+) -> Result<ProcessOutput, io::Error> {
+    let stack_size = limits.stack_size;
+    let open_files = limits.open_files;
+
+    command
+        .stdin(stdin)
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
+
+    // SAFETY: getrlimit and setrlimit are async-signal-safe, and we do not allocate in the closure
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(stack_size) = stack_size {
+                set_soft_limit(libc::RLIMIT_STACK, stack_size)?;
+            }
+            if let Some(open_files) = open_files {
+                set_soft_limit(libc::RLIMIT_NOFILE, open_files)?;
+            }
+            Ok(())
+        });
+    }
+
+    let start_time = Instant::now();
+    let child = command.spawn()?;
+    let pid = child.id() as libc::pid_t;
+    let (status, rusage) = loop {
+        if let Some(result) = try_wait(pid)? {
+            break result;
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    Ok(ProcessOutput {
+        status,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+        wall_time: start_time.elapsed(),
+        user_time: timeval_to_duration(rusage.ru_utime),
+        system_time: timeval_to_duration(rusage.ru_stime),
+        peak_memory: max_rss_to_bytes(rusage.ru_maxrss),
+        timed_out: false,
+        exceeded_output_limit: false,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Sent from the interactor to the program
+    ToProgram,
+    /// Sent from the program to the interactor
+    FromProgram,
+}
+
+pub type Transcript = Vec<(Direction, Vec<u8>)>;
+
+/// Runs the program and the interactor with the stdout of each connected to the stdin of the other.
+/// Everything they send to each other is recorded in the returned transcript.
+pub fn run_interactive(
+    program: &mut Command,
+    interactor: &mut Command,
+    limits: &Limits,
+    interactor_limits: &Limits,
+) -> Result<(ProcessOutput, ProcessOutput, Transcript), io::Error> {
+    let mut program = spawn_limited(program, Stdio::piped(), limits)?;
+    let mut interactor = match spawn_limited(interactor, Stdio::piped(), interactor_limits) {
+        Ok(interactor) => interactor,
+        Err(e) => {
+            kill_process_group(program.pid);
+            return Err(e);
+        }
+    };
+
+    let transcript = Arc::new(Mutex::new(Transcript::new()));
+    let to_program = relay(
+        interactor.take_stdout().expect("stdout is piped"),
+        program.stdin.take().expect("stdin is piped"),
+        Direction::ToProgram,
+        Arc::clone(&transcript),
+    );
+    let from_program = relay(
+        program.take_stdout().expect("stdout is piped"),
+        interactor.stdin.take().expect("stdin is piped"),
+        Direction::FromProgram,
+        Arc::clone(&transcript),
+    );
+
+    let mut program_output = program.wait()?;
+    let mut interactor_output = interactor.wait()?;
+    program_output.stdout = from_program.join().unwrap_or_default();
+    interactor_output.stdout = to_program.join().unwrap_or_default();
+
+    let transcript = std::mem::take(&mut *transcript.lock().unwrap());
+    Ok((program_output, interactor_output, transcript))
+}
+
+/// Copies everything from the reader to the writer, until either of them is closed
+fn relay(
+    mut reader: impl Read + Send + 'static,
+    mut writer: impl Write + Send + 'static,
+    direction: Direction,
+    transcript: Arc<Mutex<Transcript>>,
+) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut copied = Vec::new();
+        let mut buffer = [0; 4096];
+        while let Ok(read) = reader.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            copied.extend_from_slice(&buffer[..read]);
+            transcript
+                .lock()
+                .unwrap()
+                .push((direction, buffer[..read].to_vec()));
+            if writer
+                .write_all(&buffer[..read])
+                .and_then(|_| writer.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+        copied
+    })
+}
+
+fn set_limit(resource: Resource, value: u64) -> Result<(), io::Error> {
+    let limit = libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    };
+    if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Sets the soft limit of the resource, capped at the hard limit, which an unprivileged process can not raise
+fn set_soft_limit(resource: Resource, value: u64) -> Result<(), io::Error> {
+    // SAFETY: rlimit is a plain C struct, for which all zeroes is a valid value
+    let mut limit: libc::rlimit = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrlimit(resource, &mut limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    limit.rlim_cur = (value as libc::rlim_t).min(limit.rlim_max);
+    if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn try_wait(pid: libc::pid_t) -> Result<Option<(ExitStatus, libc::rusage)>, io::Error> {
+    let mut status = 0;
+    // SAFETY: rusage is a plain C struct, for which all zeroes is a valid value
+    let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+    match unsafe { libc::wait4(pid, &mut status, libc::WNOHANG, &mut rusage) } {
+        0 => Ok(None),
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(Some((ExitStatus::from_raw(status), rusage))),
+    }
+}
+
+fn kill_process_group(pid: libc::pid_t) {
+    // errors are ignored, as the process group might already be gone
+    unsafe {
+        libc::kill(-pid, libc::SIGKILL);
+    }
+}
+
+fn read_all(mut reader: impl Read) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    let _ = reader.read_to_end(&mut buffer);
+    buffer
+}
+
+/// Reads everything from the reader, but stops once more than `limit` bytes have been read.
+/// Returns what was read (up to the limit), and whether the limit was exceeded.
+fn read_limited(reader: impl Read, limit: Option<u64>) -> (Vec<u8>, bool) {
+    let Some(limit) = limit else {
+        return (read_all(reader), false);
+    };
+    let mut buffer = Vec::new();
+    let _ = reader.take(limit + 1).read_to_end(&mut buffer);
+    let exceeded = buffer.len() as u64 > limit;
+    buffer.truncate(limit as usize);
+    (buffer, exceeded)
+}
+
+fn timeval_to_duration(time: libc::timeval) -> Duration {
+    Duration::new(time.tv_sec as u64, time.tv_usec as u32 * 1000)
+}
+
+fn max_rss_to_bytes(max_rss: libc::c_long) -> u64 {
+    // ru_maxrss is given in kilobytes on Linux, but in bytes on macOS
+    if cfg!(target_os = "macos") {
+        max_rss as u64
+    } else {
+        max_rss as u64 * 1024
+    }
+}
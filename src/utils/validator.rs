@@ -0,0 +1,261 @@
+use color_eyre::{eyre, Report};
+
+/// The flags understood by the default output validator on Kattis, see
+/// https://www.kattis.com/problem-package-format/spec/problem_package_format#default-output-validator-specification
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidatorFlags {
+    pub case_sensitive: bool,
+    pub space_change_sensitive: bool,
+    pub float_absolute_tolerance: Option<f64>,
+    pub float_relative_tolerance: Option<f64>,
+}
+
+impl ValidatorFlags {
+    /// Parses the flags from the same format as the `validator_flags` in a Kattis problem package,
+    /// e.g. "float_tolerance 1e-6 case_sensitive"
+    pub fn parse(flags: &str) -> Result<Self, Report> {
+        let mut validator_flags = ValidatorFlags::default();
+        let mut tokens = flags.split_whitespace();
+
+        while let Some(flag) = tokens.next() {
+            match flag {
+                "case_sensitive" => validator_flags.case_sensitive = true,
+                "space_change_sensitive" => validator_flags.space_change_sensitive = true,
+                "float_absolute_tolerance" | "float_relative_tolerance" | "float_tolerance" => {
+                    let tolerance = tokens
+                        .next()
+                        .and_then(|tolerance| tolerance.parse::<f64>().ok())
+                        .ok_or_else(|| {
+                            eyre::eyre!("🙀 The validator flag {flag} must be followed by a number")
+                        })?;
+                    if flag != "float_relative_tolerance" {
+                        validator_flags.float_absolute_tolerance = Some(tolerance);
+                    }
+                    if flag != "float_absolute_tolerance" {
+                        validator_flags.float_relative_tolerance = Some(tolerance);
+                    }
+                }
+                _ => eyre::bail!("🙀 Unknown validator flag: {flag}"),
+            }
+        }
+
+        Ok(validator_flags)
+    }
+
+    pub fn has_float_tolerance(&self) -> bool {
+        self.float_absolute_tolerance.is_some() || self.float_relative_tolerance.is_some()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Judgement {
+    Accepted,
+    WrongAnswer(String),
+}
+
+struct Token<'a> {
+    // the whitespace preceding the token
+    space: &'a str,
+    text: &'a str,
+    line: usize,
+}
+
+fn tokenize(output: &str) -> (Vec<Token<'_>>, &str) {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut rest = output;
+
+    loop {
+        let space_len = rest
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(rest.len());
+        let (space, after_space) = rest.split_at(space_len);
+        line += space.matches('\n').count();
+        if after_space.is_empty() {
+            // return the trailing whitespace, as it matters when the validator is space change sensitive
+            return (tokens, space);
+        }
+
+        let text_len = after_space
+            .find(char::is_whitespace)
+            .unwrap_or(after_space.len());
+        let (text, after_text) = after_space.split_at(text_len);
+        tokens.push(Token { space, text, line });
+        rest = after_text;
+    }
+}
+
+/// Compares the output of a program to the judge answer the same way as the default output validator on Kattis,
+/// i.e. token by token, ignoring differences in whitespace and case unless told otherwise by the flags.
+pub fn validate_output(judge_answer: &str, output: &str, flags: &ValidatorFlags) -> Judgement {
+    let (judge_tokens, judge_trailing_space) = tokenize(judge_answer);
+    let (output_tokens, output_trailing_space) = tokenize(output);
+
+    for (index, judge_token) in judge_tokens.iter().enumerate() {
+        let Some(output_token) = output_tokens.get(index) else {
+            return Judgement::WrongAnswer(format!(
+                "expected `{}` on line {}, but the output ended",
+                judge_token.text, judge_token.line
+            ));
+        };
+
+        if flags.space_change_sensitive && judge_token.space != output_token.space {
+            return Judgement::WrongAnswer(format!(
+                "the whitespace before `{}` on line {} does not match the expected output",
+                output_token.text, output_token.line
+            ));
+        }
+
+        if !tokens_match(judge_token.text, output_token.text, flags) {
+            return Judgement::WrongAnswer(format!(
+                "expected `{}` but got `{}` on line {}",
+                judge_token.text, output_token.text, output_token.line
+            ));
+        }
+    }
+
+    if let Some(output_token) = output_tokens.get(judge_tokens.len()) {
+        return Judgement::WrongAnswer(format!(
+            "trailing output `{}` on line {}",
+            output_token.text, output_token.line
+        ));
+    }
+
+    if flags.space_change_sensitive && judge_trailing_space != output_trailing_space {
+        return Judgement::WrongAnswer(
+            "the whitespace at the end of the output does not match the expected output"
+                .to_string(),
+        );
+    }
+
+    Judgement::Accepted
+}
+
+fn tokens_match(judge_token: &str, output_token: &str, flags: &ValidatorFlags) -> bool {
+    if flags.has_float_tolerance() {
+        // "inf" and "nan" parse as floats too, but they are compared as plain tokens as nan is never equal to nan
+        if let Some(judge_value) = judge_token
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+        {
+            // when the judge token is a number, the output token has to be a number that is close enough
+            let Ok(output_value) = output_token.parse::<f64>() else {
+                return false;
+            };
+            let difference = (judge_value - output_value).abs();
+            let within_absolute = flags
+                .float_absolute_tolerance
+                .is_some_and(|tolerance| difference <= tolerance);
+            let within_relative = flags
+                .float_relative_tolerance
+                .is_some_and(|tolerance| difference <= tolerance * judge_value.abs());
+            return within_absolute || within_relative || judge_value == output_value;
+        }
+    }
+
+    if flags.case_sensitive {
+        judge_token == output_token
+    } else {
+        judge_token.to_lowercase() == output_token.to_lowercase()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(flags: &str) -> ValidatorFlags {
+        ValidatorFlags::parse(flags).unwrap()
+    }
+
+    fn accepted(judge_answer: &str, output: &str, validator_flags: &str) -> bool {
+        validate_output(judge_answer, output, &flags(validator_flags)) == Judgement::Accepted
+    }
+
+    #[test]
+    fn parses_flags() {
+        assert_eq!(
+            flags("float_tolerance 1e-6 case_sensitive"),
+            ValidatorFlags {
+                case_sensitive: true,
+                space_change_sensitive: false,
+                float_absolute_tolerance: Some(1e-6),
+                float_relative_tolerance: Some(1e-6),
+            }
+        );
+        assert!(ValidatorFlags::parse("float_tolerance").is_err());
+        assert!(ValidatorFlags::parse("unknown_flag").is_err());
+    }
+
+    #[test]
+    fn ignores_case_unless_case_sensitive() {
+        assert!(accepted("Yes", "YES", ""));
+        assert!(!accepted("Yes", "YES", "case_sensitive"));
+        assert!(accepted("Yes", "Yes", "case_sensitive"));
+    }
+
+    #[test]
+    fn ignores_whitespace_unless_space_change_sensitive() {
+        assert!(accepted("1 2\n3\n", "1\n2   3", ""));
+        assert!(!accepted("1 2\n3\n", "1  2\n3\n", "space_change_sensitive"));
+        assert!(!accepted("1 2\n3\n", "1 2\n3", "space_change_sensitive"));
+        assert!(accepted("1 2\n3\n", "1 2\n3\n", "space_change_sensitive"));
+    }
+
+    #[test]
+    fn compares_floats_with_absolute_tolerance() {
+        assert!(accepted(
+            "1.0",
+            "1.0000009",
+            "float_absolute_tolerance 1e-6"
+        ));
+        assert!(!accepted("1.0", "1.00001", "float_absolute_tolerance 1e-6"));
+        assert!(!accepted("1.0", "one", "float_absolute_tolerance 1e-6"));
+        // without a tolerance, numbers are compared as text
+        assert!(!accepted("1.0", "1.00", ""));
+    }
+
+    #[test]
+    fn compares_floats_with_relative_tolerance() {
+        assert!(accepted(
+            "1000000",
+            "1000000.5",
+            "float_relative_tolerance 1e-6"
+        ));
+        assert!(!accepted(
+            "1000000",
+            "1000002",
+            "float_relative_tolerance 1e-6"
+        ));
+        assert!(!accepted(
+            "0.001",
+            "0.0011",
+            "float_relative_tolerance 1e-6"
+        ));
+    }
+
+    #[test]
+    fn compares_non_finite_floats_as_tokens() {
+        assert!(accepted("nan", "NaN", "float_tolerance 1e-6"));
+        assert!(accepted("inf", "inf", "float_tolerance 1e-6"));
+        assert!(!accepted("inf", "infinity", "float_tolerance 1e-6"));
+        assert!(!accepted("1.0", "nan", "float_tolerance 1e-6"));
+    }
+
+    #[test]
+    fn reports_missing_and_extra_tokens() {
+        assert_eq!(
+            validate_output("1\n2", "1", &flags("")),
+            Judgement::WrongAnswer("expected `2` on line 2, but the output ended".to_string())
+        );
+        assert_eq!(
+            validate_output("1", "1\n2", &flags("")),
+            Judgement::WrongAnswer("trailing output `2` on line 2".to_string())
+        );
+        assert_eq!(
+            validate_output("1 2", "1 3", &flags("")),
+            Judgement::WrongAnswer("expected `2` but got `3` on line 1".to_string())
+        );
+    }
+}
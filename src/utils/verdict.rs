@@ -0,0 +1,166 @@
+use std::{os::unix::process::ExitStatusExt, process::ExitStatus};
+
+use serde::Serialize;
+
+// Messages that programs in common languages print when they run out of stack, i.e. recurse too deep
+const STACK_OVERFLOW_MESSAGES: [&str; 3] = [
+    "has overflowed its stack",
+    "RecursionError",
+    "StackOverflowError",
+];
+
+// Messages that programs in common languages print when they crash because of an uncaught exception or a panic
+const UNCAUGHT_EXCEPTION_MESSAGES: [&str; 4] = [
+    "Traceback (most recent call last)",
+    "Exception in thread",
+    "panicked at",
+    "terminate called after throwing",
+];
+
+// How many lines of a sanitizer report are shown, as sanitizers can report the same error many times
+const MAX_SANITIZER_LINES: usize = 10;
+
+/// The verdict of a test case, named like the verdicts on Kattis, so that local results and the results
+/// of submissions (see `SubmissionStatus`) are presented the same way
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Accepted,
+    WrongAnswer,
+    TimeLimitExceeded,
+    MemoryLimitExceeded,
+    OutputLimitExceeded,
+    RunTimeError,
+    CompileError,
+    /// The test could not be judged, e.g. because the output validator crashed
+    JudgeError,
+}
+
+impl Verdict {
+    /// The verdict with the given name on Kattis, e.g. "Wrong Answer"
+    pub fn from_kattis(status: &str) -> Option<Self> {
+        match status.trim() {
+            "Accepted" | "Accepted (100)" => Some(Verdict::Accepted),
+            "Wrong Answer" => Some(Verdict::WrongAnswer),
+            "Time Limit Exceeded" => Some(Verdict::TimeLimitExceeded),
+            "Memory Limit Exceeded" => Some(Verdict::MemoryLimitExceeded),
+            "Output Limit Exceeded" => Some(Verdict::OutputLimitExceeded),
+            "Run Time Error" => Some(Verdict::RunTimeError),
+            "Compile Error" => Some(Verdict::CompileError),
+            "Judge Error" => Some(Verdict::JudgeError),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verdict::Accepted => "Accepted",
+            Verdict::WrongAnswer => "Wrong Answer",
+            Verdict::TimeLimitExceeded => "Time Limit Exceeded",
+            Verdict::MemoryLimitExceeded => "Memory Limit Exceeded",
+            Verdict::OutputLimitExceeded => "Output Limit Exceeded",
+            Verdict::RunTimeError => "Run Time Error",
+            Verdict::CompileError => "Compile Error",
+            Verdict::JudgeError => "Judge Error",
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            Verdict::Accepted => "✅",
+            Verdict::WrongAnswer => "💔",
+            Verdict::TimeLimitExceeded => "⌛️",
+            Verdict::MemoryLimitExceeded => "🧠",
+            Verdict::OutputLimitExceeded => "🌊",
+            Verdict::RunTimeError => "💥",
+            Verdict::CompileError => "🆘",
+            Verdict::JudgeError => "🔮",
+        }
+    }
+}
+
+/// How a program that crashed exited, and what the likely cause of that is
+pub struct Crash {
+    /// e.g. "exit code 1" or "Segmentation fault (SIGSEGV)"
+    pub reason: String,
+    pub hint: &'static str,
+}
+
+/// Decodes the exit code or signal of a program that crashed, using its error output to tell e.g. stack overflows
+/// and uncaught exceptions apart
+pub fn describe_crash(status: &ExitStatus, stderr: &str) -> Crash {
+    let stack_overflow = STACK_OVERFLOW_MESSAGES
+        .iter()
+        .any(|message| stderr.contains(message));
+    let uncaught_exception = UNCAUGHT_EXCEPTION_MESSAGES
+        .iter()
+        .any(|message| stderr.contains(message));
+    let sanitizer_error = sanitizer_report(stderr).is_some();
+
+    let reason = match status.signal() {
+        Some(libc::SIGSEGV) => "Segmentation fault (SIGSEGV)".to_string(),
+        Some(libc::SIGFPE) => "Floating point exception (SIGFPE)".to_string(),
+        Some(libc::SIGABRT) => "Aborted (SIGABRT)".to_string(),
+        Some(libc::SIGBUS) => "Bus error (SIGBUS)".to_string(),
+        Some(libc::SIGILL) => "Illegal instruction (SIGILL)".to_string(),
+        Some(libc::SIGKILL) => "Killed (SIGKILL)".to_string(),
+        Some(signal) => format!("signal {signal}"),
+        None => format!("exit code {}", status.code().unwrap_or_default()),
+    };
+    let reason = if stack_overflow {
+        format!("Stack overflow, {reason}")
+    } else {
+        reason
+    };
+
+    let hint = if sanitizer_error {
+        "A sanitizer found a bug in the program - have a look at its report to see what and where."
+    } else if stack_overflow {
+        "The recursion is probably too deep - can it be turned into a loop?"
+    } else {
+        match status.signal() {
+            Some(libc::SIGSEGV) | Some(libc::SIGBUS) => {
+                "The program accessed memory it should not - is an array index out of bounds, or is the recursion too deep?"
+            }
+            Some(libc::SIGFPE) => "Is there an integer division or modulo by zero?",
+            Some(libc::SIGABRT) => {
+                "Did an assertion fail, or was an exception not caught? Have a look at the error output."
+            }
+            Some(libc::SIGKILL) => {
+                "The program was killed from the outside, e.g. because the system ran out of memory."
+            }
+            Some(_) => "The program was stopped by a signal - have a look at the error output.",
+            None if uncaught_exception => {
+                "An exception was not caught - have a look at the error output to see where."
+            }
+            None => "Kattis also counts a non-zero exit code as a Run Time Error - does your program return 0?",
+        }
+    };
+    Crash { reason, hint }
+}
+
+/// The lines of the error output where a sanitizer (e.g. AddressSanitizer or UndefinedBehaviorSanitizer) reports
+/// an error, without the stack traces, if there are any
+pub fn sanitizer_report(stderr: &str) -> Option<String> {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in stderr.lines() {
+        let is_report = line.contains("runtime error:")
+            || (line.contains("Sanitizer")
+                && ["ERROR:", "WARNING:", "SUMMARY:"]
+                    .iter()
+                    .any(|marker| line.contains(marker)));
+        if is_report && !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    let omitted = lines.len().saturating_sub(MAX_SANITIZER_LINES);
+    lines.truncate(MAX_SANITIZER_LINES);
+    let mut report = lines.join("\n");
+    if omitted > 0 {
+        report.push_str(&format!("\n... and {omitted} more"));
+    }
+    Some(report)
+}
@@ -39,12 +39,28 @@ pub async fn problem_exists(
     problem: &str,
     hostname: &str,
 ) -> Result<bool, Report> {
+    Ok(get_problem_page(http_client, problem, hostname)
+        .await?
+        .is_some())
+}
+
+/// Gets the page of the problem, or `None` if the problem does not exist
+pub async fn get_problem_page(
+    http_client: &HttpClient,
+    problem: &str,
+    hostname: &str,
+) -> Result<Option<String>, Report> {
     let problem_url = get_problem_url_from_hostname(problem, hostname);
     let response = http_client.client.get(&problem_url).send().await?;
 
     match response.status() {
-        StatusCode::OK => Ok(true),
-        StatusCode::NOT_FOUND => Ok(false),
+        StatusCode::OK => Ok(Some(
+            response
+                .text()
+                .await
+                .wrap_err("🙀 Failed to get response body")?,
+        )),
+        StatusCode::NOT_FOUND => Ok(None),
         _ => {
             let status = response.status();
             eyre::bail!("🙀 Failed to get problem: {} - {}", problem, status)